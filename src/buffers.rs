@@ -4,36 +4,77 @@ use wgpu::{BindGroup, BindGroupLayout, Buffer, Device, Queue, Texture, util::Dev
 
 use super::image_texture::*;
 
+// texture index used by materials for optional texture maps that are not set
+pub const NO_TEXTURE: u32 = u32::MAX;
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Params {
-    pub screen_width: u32,       // float, aligned to 4 bytes
-    pub accumulation_index: u32, // u32, aligned to 4 bytes
-    pub accumulate: u32,         // u32, aligned to 4 bytes
-    pub sphere_count: u32,       // u32, aligned to 4 bytes
-    pub object_count: u32,       // u32, aligned to 4 bytes
-    pub compute_per_frame: u32,  // u32, aligned to 4 bytes
-    pub texture_width: u32,      // u32, aligned to 4 bytes
-    pub texture_height: u32,     // u32, aligned to 4 bytes
-    pub textue_count: u32,       // u32, aligned to 4 bytes
-    pub env_map_width: u32,      // u32, aligned to 4 bytes
-    pub env_map_height: u32,     // u32, aligned to 4 bytes
-    pub _padding: [u8; 4],       // padding to ensure 16-byte alignment
+    pub screen_width: u32,             // float, aligned to 4 bytes
+    pub accumulated_samples: u32,      // u32, aligned to 4 bytes
+    pub accumulate: u32,               // u32, aligned to 4 bytes
+    pub sphere_count: u32,             // u32, aligned to 4 bytes
+    pub object_count: u32,             // u32, aligned to 4 bytes
+    pub compute_per_frame: u32,        // u32, aligned to 4 bytes, samples of this dispatch
+    pub texture_width: u32,            // u32, aligned to 4 bytes
+    pub texture_height: u32,           // u32, aligned to 4 bytes
+    pub textue_count: u32,             // u32, aligned to 4 bytes
+    pub env_map_width: u32,            // u32, aligned to 4 bytes
+    pub env_map_height: u32,           // u32, aligned to 4 bytes
+    pub emitter_count: u32,            // u32, aligned to 4 bytes
+    pub light_count: u32,              // u32, aligned to 4 bytes
+    pub max_diffuse_bounces: u32,      // u32, aligned to 4 bytes
+    pub max_specular_bounces: u32,     // u32, aligned to 4 bytes
+    pub max_transmission_bounces: u32, // u32, aligned to 4 bytes
+    pub roulette_depth: u32,           // u32, aligned to 4 bytes
+    pub screen_height: u32,            // u32, aligned to 4 bytes
+    pub filter_type: u32,              // u32, aligned to 4 bytes
+    pub filter_radius: f32,            // f32, aligned to 4 bytes
+    pub motion_blur: u32,              // u32, aligned to 4 bytes
+    pub seed: u32,                     // u32, aligned to 4 bytes
+    pub tile_x: u32,                   // u32, aligned to 4 bytes
+    pub tile_y: u32,                   // u32, aligned to 4 bytes
+    pub tile_width: u32,               // u32, aligned to 4 bytes
+    pub tile_height: u32,              // u32, aligned to 4 bytes
 }
 
+// pixel reconstruction filters, same values as in the compute shader
+pub const FILTER_BOX: u32 = 0;
+pub const FILTER_TENT: u32 = 1;
+pub const FILTER_GAUSSIAN: u32 = 2;
+pub const FILTER_BLACKMAN_HARRIS: u32 = 3;
+
+// tone mappers for the displayed image, same values as in the compute shader
+pub const TONE_MAP_CLAMP: u32 = 0;
+pub const TONE_MAP_REINHARD: u32 = 1;
+pub const TONE_MAP_ACES: u32 = 2;
+pub const TONE_MAP_AGX: u32 = 3;
+pub const TONE_MAP_PBR_NEUTRAL: u32 = 4;
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct RayCamera {
-    pub origin: [f32; 3],  // vec3, aligned to 12 bytes
-    pub _padding: [u8; 4], // padding to ensure 16-byte alignment
+    pub inverse_projection: [[f32; 4]; 4], // mat4x4, aligned to 16 bytes
+    pub inverse_view: [[f32; 4]; 4],       // mat4x4, aligned to 16 bytes
+    pub end_inverse_view: [[f32; 4]; 4],   // mat4x4, aligned to 16 bytes
+    pub origin: [f32; 3],                  // vec3, aligned to 12 bytes
+    pub aperture_radius: f32,              // f32, aligned to 4 bytes
+    pub focus_distance: f32,               // f32, aligned to 4 bytes
+    pub aperture_blades: u32,              // u32, aligned to 4 bytes
+    pub projection_type: u32,              // u32, aligned to 4 bytes
+    pub orthographic_height: f32,          // f32, aligned to 4 bytes
+    pub end_origin: [f32; 3],              // vec3, aligned to 12 bytes
+    pub fisheye_fov: f32,                  // f32, aligned to 4 bytes
+    pub near_clip: f32,                    // f32, aligned to 4 bytes
+    pub far_clip: f32,                     // f32, aligned to 4 bytes
+    pub _padding: [u8; 8],                 // padding to ensure 16-byte alignment
 }
 
-#[repr(C)]
-#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct Ray {
-    pub direction: [f32; 3], // vec3, aligned to 12 bytes
-    pub _padding: [u8; 4],   // padding to ensure 16-byte alignment
-}
+// camera projections, same values as in the compute shader
+pub const PROJECTION_PERSPECTIVE: u32 = 0;
+pub const PROJECTION_ORTHOGRAPHIC: u32 = 1;
+pub const PROJECTION_EQUIRECTANGULAR: u32 = 2;
+pub const PROJECTION_FISHEYE: u32 = 3;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -61,10 +102,12 @@ pub struct SceneTriangle {
     _padding6: [u8; 4],       // padding to ensure 16-byte alignment
     pub max_bounds: [f32; 3], // vec3, aligned to 12 bytes
     _padding7: [u8; 4],       // padding to ensure 16-byte alignment
+    tangent: [f32; 3],        // vec3, aligned to 12 bytes
+    tangent_sign: f32,        // f32, aligned to 4 bytes
 }
 
 impl SceneTriangle {
-    pub fn new(a: Vec3A, b: Vec3A, c: Vec3A) -> SceneTriangle {
+    pub fn new(a: Vec3A, b: Vec3A, c: Vec3A, tangent: Vec3A, tangent_sign: f32) -> SceneTriangle {
         // precalculations to save on compute
 
         let edge_ab = b - a;
@@ -91,7 +134,59 @@ impl SceneTriangle {
             _padding6: [0; 4],               // padding to ensure 16-byte alignment
             max_bounds: max_bounds.into(),   // vec3, aligned to 12 bytes
             _padding7: [0; 4],               // padding to ensure 16-byte alignment
+            tangent: tangent.into(),         // vec3, aligned to 12 bytes
+            tangent_sign,                    // f32, aligned to 4 bytes
+        }
+    }
+
+    pub fn intersect(&self, origin: Vec3A, direction: Vec3A) -> Option<f32> {
+        // möller trumbore ray triangle intersection
+        let edge_ab = Vec3A::from(self.edge_ab);
+        let edge_ac = Vec3A::from(self.edge_ac);
+
+        let p = direction.cross(edge_ac);
+        let determinant = edge_ab.dot(p);
+
+        if determinant.abs() < 1e-8 {
+            return None;
+        }
+
+        let to_origin = origin - Vec3A::from(self.a);
+        let u = to_origin.dot(p) / determinant;
+
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = to_origin.cross(edge_ab);
+        let v = direction.dot(q) / determinant;
+
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = edge_ac.dot(q) / determinant;
+        (distance > 0.0).then_some(distance)
+    }
+}
+
+impl SceneSphere {
+    pub fn intersect(&self, origin: Vec3A, direction: Vec3A) -> Option<f32> {
+        let to_origin = origin - Vec3A::from(self.position);
+
+        let b = to_origin.dot(direction);
+        let c = to_origin.length_squared() - self.radius * self.radius;
+        let discriminant = b * b - c;
+
+        if discriminant < 0.0 {
+            return None;
         }
+
+        // the closest hit in front of the origin
+        let root = discriminant.sqrt();
+        [-b - root, -b + root]
+            .into_iter()
+            .find(|distance| *distance > 0.0)
     }
 }
 
@@ -105,18 +200,74 @@ pub struct SceneMaterial {
     pub specular_scatter: f32, // f32, aligned to 4 bytes
     pub glass: f32,            // f32, aligned to 4 bytes
     pub refraction_index: f32, // f32, aligned to 4 bytes
-    pub _padding: [u8; 4],     // padding to ensure 16-byte alignment
+    pub normal_map_index: u32, // u32, aligned to 4 bytes
+    pub height_map_index: u32, // u32, aligned to 4 bytes
+    pub normal_strength: f32,  // f32, aligned to 4 bytes
+    // the scalar values above are multiplied by these maps when they are set,
+    // channels follow the glTF ORM packing so one texture can be used for both roughness and specular,
+    // there is no metallic value, the metalness in the blue channel scales the specular reflection chance
+    pub roughness_map_index: u32, // u32, aligned to 4 bytes, green channel
+    pub specular_map_index: u32,  // u32, aligned to 4 bytes, blue channel
+    pub emission_map_index: u32,  // u32, aligned to 4 bytes, rgb color
+    pub _padding: [u8; 12],       // padding to ensure 16-byte alignment
 }
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct ObjectInfo {
-    pub min_bounds: [f32; 3],        // vec3, aligned to 12 bytes
-    pub first_sub_object_index: u32, // f32, aligned to 4 bytes
-    pub max_bounds: [f32; 3],        // vec3, aligned to 12 bytes
-    pub sub_object_count: u32,       // f32, aligned to 4 bytes
-    pub material_index: u32,         // f32, aligned to 4 bytes
-    pub _padding: [u8; 12],          // padding to ensure 16-byte alignment
+    pub min_bounds: [f32; 3],         // vec3, aligned to 12 bytes
+    pub first_sub_object_index: u32,  // f32, aligned to 4 bytes
+    pub max_bounds: [f32; 3],         // vec3, aligned to 12 bytes
+    pub sub_object_count: u32,        // f32, aligned to 4 bytes
+    pub material_index: u32,          // f32, aligned to 4 bytes
+    pub moving: u32,                  // u32, aligned to 4 bytes
+    pub _padding: [u8; 8],            // padding to ensure 16-byte alignment
+    pub swept_min_bounds: [f32; 3],   // vec3, aligned to 12 bytes
+    pub _padding2: [u8; 4],           // padding to ensure 16-byte alignment
+    pub swept_max_bounds: [f32; 3],   // vec3, aligned to 12 bytes
+    pub _padding3: [u8; 4],           // padding to ensure 16-byte alignment
+    pub translation: [f32; 3],        // vec3, aligned to 12 bytes
+    pub _padding4: [u8; 4],           // padding to ensure 16-byte alignment
+    pub rotation: [f32; 3],           // vec3, aligned to 12 bytes
+    pub _padding5: [u8; 4],           // padding to ensure 16-byte alignment
+    pub motion_translation: [f32; 3], // vec3, aligned to 12 bytes
+    pub _padding6: [u8; 4],           // padding to ensure 16-byte alignment
+    pub motion_rotation: [f32; 3],    // vec3, aligned to 12 bytes
+    pub _padding7: [u8; 4],           // padding to ensure 16-byte alignment
+}
+
+// primitive types of emitters, same values as in the compute shader
+pub const PRIMITIVE_SPHERE: u32 = 0;
+pub const PRIMITIVE_TRIANGLE: u32 = 1;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SceneEmitter {
+    pub primitive_type: u32,  // u32, aligned to 4 bytes
+    pub primitive_index: u32, // u32, aligned to 4 bytes
+    pub object_index: u32,    // u32, aligned to 4 bytes, moves the triangles of moving objects
+    pub _padding: [u8; 4],    // padding to ensure 16-byte alignment
+}
+
+// analytic light types, same values as in the compute shader
+pub const LIGHT_POINT: u32 = 0;
+pub const LIGHT_SPOT: u32 = 1;
+pub const LIGHT_DIRECTIONAL: u32 = 2;
+pub const LIGHT_RECTANGLE: u32 = 3;
+pub const LIGHT_DISK: u32 = 4;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SceneLight {
+    pub position: [f32; 3], // vec3, aligned to 12 bytes, unused for directional lights
+    pub light_type: u32,    // u32, aligned to 4 bytes
+    pub direction: [f32; 3], // vec3, aligned to 12 bytes, where spot, directional and area lights point to
+    pub intensity: f32,      // f32, aligned to 4 bytes
+    pub color: [f32; 3],     // vec3, aligned to 12 bytes
+    pub spot_angle: f32,     // f32, aligned to 4 bytes, outer cone angle in degrees
+    pub size: [f32; 2],      // vec2, aligned to 8 bytes, rectangle width and height or disk radius
+    pub spot_softness: f32,  // f32, aligned to 4 bytes, part of the cone that fades out
+    pub _padding: [u8; 4],   // padding to ensure 16-byte alignment
 }
 
 #[repr(C)]
@@ -137,21 +288,63 @@ macro_rules! bind_group_entry {
     };
 }
 
+macro_rules! texture_bind_group_entry {
+    ($binding:expr, $texture:expr) => {
+        wgpu::BindGroupEntry {
+            binding: $binding,
+            resource: wgpu::BindingResource::TextureView(
+                &$texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            ),
+        }
+    };
+}
+
+// bindings of the compute shader
+const PARAMS_BIND: u32 = 0;
+const CAMERA_BIND: u32 = 3;
+const MATERIAL_BIND: u32 = 4;
+const SPHERE_BIND: u32 = 5;
+const ACCUMULATION_BIND: u32 = 6;
+const TRIANGLE_BIND: u32 = 7;
+const OBJECT_BIND: u32 = 8;
+const TEXTURE_BIND: u32 = 9;
+const SUB_OBJECT_BIND: u32 = 10;
+const ENV_MAP_BIND: u32 = 11;
+const EMITTER_BIND: u32 = 12;
+const LIGHT_BIND: u32 = 13;
+const RADIANCE_TEXTURE_BIND: u32 = 14;
+const ALBEDO_TEXTURE_BIND: u32 = 15;
+const NORMAL_DEPTH_TEXTURE_BIND: u32 = 16;
+const ALBEDO_ACCUMULATION_BIND: u32 = 17;
+const NORMAL_DEPTH_ACCUMULATION_BIND: u32 = 18;
+
+// formats of the per pixel textures, the radiance and depth need more range than the displayed colors
+pub const OUTPUT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const RADIANCE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+pub const ALBEDO_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+pub const NORMAL_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+
 pub struct DataBuffers {
-    pub output_buffer_size: u64,
     pub accumulation_buffer_size: u64,
-    pub ray_buffer: Buffer,
-    pub output_buffer: Buffer,
+    pub output_texture: Texture,
+    pub radiance_texture: Texture,
+    pub albedo_texture: Texture,
+    pub normal_depth_texture: Texture,
     pub params_buffer: Buffer,
     pub camera_buffer: Buffer,
     pub material_buffer: Buffer,
     pub sphere_buffer: Buffer,
     pub accumulation_buffer: Buffer,
+    // sums of the denoiser guides, averaged over the same samples as the radiance
+    pub albedo_accumulation_buffer: Buffer,
+    pub normal_depth_accumulation_buffer: Buffer,
     pub triangle_buffer: Buffer,
     pub object_buffer: Buffer,
     pub sub_object_buffer: Buffer,
     pub image_textures: Texture,
     pub environment_map: Texture,
+    pub emitter_buffer: Buffer,
+    pub light_buffer: Buffer,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -160,30 +353,21 @@ impl DataBuffers {
         device: &wgpu::Device,
         size: &winit::dpi::PhysicalSize<u32>,
         camera: RayCamera,
-        camera_rays: &[Ray],
         material_array: &[SceneMaterial],
         sphere_array: &[SceneSphere],
         triangle_array: &[SceneTriangle],
         object_array: &[ObjectInfo],
         sub_object_array: &[SubObjectInfo],
+        emitter_capacity: usize,
+        light_capacity: usize,
         params: &[Params],
     ) -> (DataBuffers, BindGroupLayout, BindGroup) {
-        let ray_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Ray Buffer"),
-            contents: bytemuck::cast_slice(camera_rays),
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-        });
-
-        // 4 bytes of u8 per pixel, RGBA
-        let output_buffer_size = (size.width * size.height * std::mem::size_of::<[u8; 4]>() as u32)
-            as wgpu::BufferAddress;
-
-        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Output Buffer"),
-            size: output_buffer_size,
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
-            mapped_at_creation: false,
-        });
+        let output_texture = create_pixel_texture(device, size.width, size.height, OUTPUT_FORMAT);
+        let radiance_texture =
+            create_pixel_texture(device, size.width, size.height, RADIANCE_FORMAT);
+        let albedo_texture = create_pixel_texture(device, size.width, size.height, ALBEDO_FORMAT);
+        let normal_depth_texture =
+            create_pixel_texture(device, size.width, size.height, NORMAL_DEPTH_FORMAT);
 
         let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Params Buffer"),
@@ -209,17 +393,12 @@ impl DataBuffers {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        // 4 bytes of RGBA f32 per pixel
-        let accumulation_buffer_size =
-            (size.width * size.height * std::mem::size_of::<[f32; 4]>() as u32)
-                as wgpu::BufferAddress;
-
-        let accumulation_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Accumulation Buffer"),
-            size: accumulation_buffer_size,
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        let (accumulation_buffer, accumulation_buffer_size) =
+            create_accumulation_buffer(device, size.width, size.height);
+        let (albedo_accumulation_buffer, _) =
+            create_accumulation_buffer(device, size.width, size.height);
+        let (normal_depth_accumulation_buffer, _) =
+            create_accumulation_buffer(device, size.width, size.height);
 
         let triangle_buffer: Buffer =
             device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -246,7 +425,9 @@ impl DataBuffers {
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            // stored as linear data so normal and height maps are not gamma decoded,
+            // color textures are converted from sRGB in the compute shader
+            format: wgpu::TextureFormat::Rgba8Unorm,
             usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
             view_formats: &[],
         });
@@ -275,205 +456,119 @@ impl DataBuffers {
             view_formats: &[],
         });
 
+        // every sphere and triangle can become an emitter when its material is changed,
+        // so the buffer is made big enough to hold all of them
+        let emitter_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Emitter Buffer"),
+            size: (emitter_capacity.max(1) * std::mem::size_of::<SceneEmitter>())
+                as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // lights can be added in the UI, the unused ones stay zeroed and are not counted in the params
+        let light_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Light Buffer"),
+            size: (light_capacity * std::mem::size_of::<SceneLight>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
         let buffers = DataBuffers {
-            output_buffer_size,
             accumulation_buffer_size,
-            ray_buffer,
-            output_buffer,
+            output_texture,
+            radiance_texture,
+            albedo_texture,
+            normal_depth_texture,
             params_buffer,
             camera_buffer,
             material_buffer,
             sphere_buffer,
             accumulation_buffer,
+            albedo_accumulation_buffer,
+            normal_depth_accumulation_buffer,
             triangle_buffer,
             object_buffer,
             sub_object_buffer,
             image_textures,
             environment_map,
+            emitter_buffer,
+            light_buffer,
         };
 
-        let (bind_group_layout, compute_bind_group) = buffers.create_compute_bindgroup(device);
+        let bind_group_layout = create_compute_bindgroup_layout(device);
+        let compute_bind_group = buffers.create_compute_bindgroup(device, &bind_group_layout);
 
         (buffers, bind_group_layout, compute_bind_group)
     }
 
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        bind_group_layout: &BindGroupLayout,
+    ) -> BindGroup {
+        // the per pixel buffers are made again for the new size, so the bind group has to be made again too
+        self.output_texture = create_pixel_texture(device, width, height, OUTPUT_FORMAT);
+        self.radiance_texture = create_pixel_texture(device, width, height, RADIANCE_FORMAT);
+        self.albedo_texture = create_pixel_texture(device, width, height, ALBEDO_FORMAT);
+        self.normal_depth_texture =
+            create_pixel_texture(device, width, height, NORMAL_DEPTH_FORMAT);
+        (self.accumulation_buffer, self.accumulation_buffer_size) =
+            create_accumulation_buffer(device, width, height);
+        (self.albedo_accumulation_buffer, _) = create_accumulation_buffer(device, width, height);
+        (self.normal_depth_accumulation_buffer, _) =
+            create_accumulation_buffer(device, width, height);
+
+        self.create_compute_bindgroup(device, bind_group_layout)
+    }
+
     fn create_compute_bindgroup(
         &self,
         device: &wgpu::Device,
-    ) -> (wgpu::BindGroupLayout, BindGroup) {
-        let params_bind = 0;
-        let ray_directions_bind = 1;
-        let pixel_colors_bind = 2;
-        let camera_bind = 3;
-        let material_bind = 4;
-        let sphere_bind = 5;
-        let accumulation_bind = 6;
-        let triangle_bind = 7;
-        let object_bind = 8;
-        let texture_bind = 9;
-        let sub_object_bind = 10;
-        let env_map_bind = 11;
-
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        bind_group_layout: &BindGroupLayout,
+    ) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
             entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: params_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: ray_directions_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: pixel_colors_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: false },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: camera_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: material_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: sphere_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: accumulation_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: false },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: triangle_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: object_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: texture_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2Array,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: sub_object_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Storage { read_only: true },
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: env_map_bind,
-                    visibility: wgpu::ShaderStages::COMPUTE,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
-            ],
-            label: None,
-        });
-
-        let compute_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &bind_group_layout,
-            entries: &[
-                bind_group_entry!(params_bind, self.params_buffer),
-                bind_group_entry!(ray_directions_bind, self.ray_buffer),
-                bind_group_entry!(pixel_colors_bind, self.output_buffer),
-                bind_group_entry!(camera_bind, self.camera_buffer),
-                bind_group_entry!(material_bind, self.material_buffer),
-                bind_group_entry!(sphere_bind, self.sphere_buffer),
-                bind_group_entry!(accumulation_bind, self.accumulation_buffer),
-                bind_group_entry!(triangle_bind, self.triangle_buffer),
-                bind_group_entry!(object_bind, self.object_buffer),
+                bind_group_entry!(PARAMS_BIND, self.params_buffer),
+                bind_group_entry!(CAMERA_BIND, self.camera_buffer),
+                bind_group_entry!(MATERIAL_BIND, self.material_buffer),
+                bind_group_entry!(SPHERE_BIND, self.sphere_buffer),
+                bind_group_entry!(ACCUMULATION_BIND, self.accumulation_buffer),
+                bind_group_entry!(TRIANGLE_BIND, self.triangle_buffer),
+                bind_group_entry!(OBJECT_BIND, self.object_buffer),
                 wgpu::BindGroupEntry {
-                    binding: texture_bind,
+                    binding: TEXTURE_BIND,
                     resource: wgpu::BindingResource::TextureView(
                         &self
                             .image_textures
                             .create_view(&wgpu::TextureViewDescriptor::default()),
                     ),
                 },
-                bind_group_entry!(sub_object_bind, self.sub_object_buffer),
+                bind_group_entry!(SUB_OBJECT_BIND, self.sub_object_buffer),
                 wgpu::BindGroupEntry {
-                    binding: env_map_bind,
+                    binding: ENV_MAP_BIND,
                     resource: wgpu::BindingResource::TextureView(
                         &self
                             .environment_map
                             .create_view(&wgpu::TextureViewDescriptor::default()),
                     ),
                 },
+                bind_group_entry!(EMITTER_BIND, self.emitter_buffer),
+                bind_group_entry!(LIGHT_BIND, self.light_buffer),
+                texture_bind_group_entry!(RADIANCE_TEXTURE_BIND, self.radiance_texture),
+                texture_bind_group_entry!(ALBEDO_TEXTURE_BIND, self.albedo_texture),
+                texture_bind_group_entry!(NORMAL_DEPTH_TEXTURE_BIND, self.normal_depth_texture),
+                bind_group_entry!(ALBEDO_ACCUMULATION_BIND, self.albedo_accumulation_buffer),
+                bind_group_entry!(
+                    NORMAL_DEPTH_ACCUMULATION_BIND,
+                    self.normal_depth_accumulation_buffer
+                ),
             ],
             label: None,
-        });
-
-        (bind_group_layout, compute_bind_group)
+        })
     }
 
     pub fn update_texture_buffer(
@@ -538,22 +633,38 @@ impl DataBuffers {
         );
     }
 
-    pub fn update_ray_directions(&self, queue: &Queue, new_rays: &[Ray]) {
-        queue.write_buffer(&self.ray_buffer, 0, bytemuck::cast_slice(new_rays));
-    }
-
     pub fn reset_accumulation(&mut self, device: &Device, queue: &Queue, params: &[Params]) {
         let mut buffer_encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Buffer Encoder"),
         });
 
         buffer_encoder.clear_buffer(&self.accumulation_buffer, 0, None);
+        buffer_encoder.clear_buffer(&self.albedo_accumulation_buffer, 0, None);
+        buffer_encoder.clear_buffer(&self.normal_depth_accumulation_buffer, 0, None);
 
         queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(params));
 
         queue.submit(Some(buffer_encoder.finish()));
     }
 
+    pub fn read_output_texture(&self, device: &Device, queue: &Queue) -> Vec<[f32; 4]> {
+        // tone mapped colors of the displayed image
+        read_texture(device, queue, &self.output_texture)
+    }
+
+    pub fn read_accumulation_buffer(&self, device: &Device, queue: &Queue) -> Vec<[f32; 4]> {
+        // sums of the linear radiance of every sample, one rgba f32 per pixel
+        let size = self.accumulation_buffer_size;
+
+        let data = read_buffer(device, queue, size, |encoder, staging_buffer| {
+            encoder.copy_buffer_to_buffer(&self.accumulation_buffer, 0, staging_buffer, 0, size);
+        });
+
+        data.chunks_exact(std::mem::size_of::<[f32; 4]>())
+            .map(bytemuck::pod_read_unaligned)
+            .collect()
+    }
+
     pub fn update_accumulation(&self, queue: &Queue, params: &[Params]) {
         queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(params));
     }
@@ -586,6 +697,14 @@ impl DataBuffers {
         );
     }
 
+    pub fn update_emitters(&self, queue: &Queue, new_emitters: &[SceneEmitter]) {
+        queue.write_buffer(&self.emitter_buffer, 0, bytemuck::cast_slice(new_emitters));
+    }
+
+    pub fn update_lights(&self, queue: &Queue, new_lights: &[SceneLight]) {
+        queue.write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(new_lights));
+    }
+
     pub fn update_materials(&self, queue: &Queue, new_materials: &[SceneMaterial]) {
         queue.write_buffer(
             &self.material_buffer,
@@ -594,3 +713,344 @@ impl DataBuffers {
         );
     }
 }
+
+fn read_buffer(
+    device: &Device,
+    queue: &Queue,
+    size: u64,
+    copy: impl FnOnce(&mut wgpu::CommandEncoder, &Buffer),
+) -> Vec<u8> {
+    // copies gpu data to a buffer the cpu can map and waits for the copy
+    let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Staging Buffer"),
+        size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Readback Encoder"),
+    });
+
+    copy(&mut encoder, &staging_buffer);
+    queue.submit(Some(encoder.finish()));
+
+    let buffer_slice = staging_buffer.slice(..);
+    buffer_slice.map_async(wgpu::MapMode::Read, |result| {
+        result.expect("could not map the staging buffer");
+    });
+
+    device.poll(wgpu::Maintain::Wait);
+
+    let data = buffer_slice.get_mapped_range().to_vec();
+    staging_buffer.unmap();
+
+    data
+}
+
+pub fn read_texture(device: &Device, queue: &Queue, texture: &Texture) -> Vec<[f32; 4]> {
+    // reads a rgba f16 or f32 texture back to the cpu, one rgba f32 per pixel
+    let size = texture.size();
+    let is_half = texture.format() == wgpu::TextureFormat::Rgba16Float;
+
+    let bytes_per_pixel = if is_half {
+        std::mem::size_of::<[u16; 4]>() as u32
+    } else {
+        std::mem::size_of::<[f32; 4]>() as u32
+    };
+    let bytes_per_row = padded_bytes_per_row(size.width, bytes_per_pixel);
+
+    // copies from a texture to a buffer need rows that are a multiple of 256 bytes
+    let data = read_buffer(
+        device,
+        queue,
+        (bytes_per_row * size.height) as wgpu::BufferAddress,
+        |encoder, staging_buffer| {
+            encoder.copy_texture_to_buffer(
+                texture.as_image_copy(),
+                wgpu::ImageCopyBuffer {
+                    buffer: staging_buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(bytes_per_row),
+                        rows_per_image: Some(size.height),
+                    },
+                },
+                size,
+            );
+        },
+    );
+
+    // the padding at the end of every row is removed
+    let row_size = (size.width * bytes_per_pixel) as usize;
+
+    data.chunks_exact(bytes_per_row as usize)
+        .flat_map(|row| row[..row_size].chunks_exact(bytes_per_pixel as usize))
+        .map(|pixel| {
+            if is_half {
+                let channels: [u16; 4] = bytemuck::pod_read_unaligned(pixel);
+                channels.map(|channel| half::f16::from_bits(channel).to_f32())
+            } else {
+                bytemuck::pod_read_unaligned(pixel)
+            }
+        })
+        .collect()
+}
+
+fn padded_bytes_per_row(width: u32, bytes_per_pixel: u32) -> u32 {
+    // rows of a buffer copied from or to a texture have to be a multiple of 256 bytes
+    let value = width * bytes_per_pixel;
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    (value + alignment - 1) & !(alignment - 1)
+}
+
+pub fn create_pixel_texture(
+    device: &wgpu::Device,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> Texture {
+    // one texel per rendered pixel, written by a compute shader and read by the next pass or the cpu
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Pixel Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::STORAGE_BINDING
+            | wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+pub fn pixel_buffer_limits(limits: &wgpu::Limits) -> (u64, u32) {
+    // most pixels and longest side the f32 rgba accumulation buffer and the pixel textures can have
+    let max_pixels =
+        limits.max_storage_buffer_binding_size as u64 / std::mem::size_of::<[f32; 4]>() as u64;
+
+    (max_pixels, limits.max_texture_dimension_2d)
+}
+
+pub fn fit_pixel_buffer_limits(
+    size: winit::dpi::PhysicalSize<u32>,
+    limits: &wgpu::Limits,
+) -> winit::dpi::PhysicalSize<u32> {
+    // shrinks the size with the same aspect ratio until the pixel buffers fit the device limits
+    let (max_pixels, max_length) = pixel_buffer_limits(limits);
+    let pixels = size.width as u64 * size.height as u64;
+
+    let scale = (max_length as f64 / size.width as f64)
+        .min(max_length as f64 / size.height as f64)
+        .min((max_pixels as f64 / pixels as f64).sqrt())
+        .min(1.0);
+
+    let fit = |length: u32| ((length as f64 * scale) as u32).max(1);
+    winit::dpi::PhysicalSize::new(fit(size.width), fit(size.height))
+}
+
+fn create_accumulation_buffer(device: &wgpu::Device, width: u32, height: u32) -> (Buffer, u64) {
+    // 16 bytes of RGBA f32 per pixel, counted in u64 so large images can not overflow
+    let accumulation_buffer_size = width as wgpu::BufferAddress
+        * height as wgpu::BufferAddress
+        * std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress;
+
+    let accumulation_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Accumulation Buffer"),
+        size: accumulation_buffer_size,
+        usage: wgpu::BufferUsages::STORAGE
+            | wgpu::BufferUsages::COPY_DST
+            | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+
+    (accumulation_buffer, accumulation_buffer_size)
+}
+
+fn create_compute_bindgroup_layout(device: &wgpu::Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: PARAMS_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: CAMERA_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: MATERIAL_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: SPHERE_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: ACCUMULATION_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: TRIANGLE_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: OBJECT_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: TEXTURE_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2Array,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: SUB_OBJECT_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: ENV_MAP_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: EMITTER_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: LIGHT_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            storage_texture_layout_entry(RADIANCE_TEXTURE_BIND, RADIANCE_FORMAT),
+            storage_texture_layout_entry(ALBEDO_TEXTURE_BIND, ALBEDO_FORMAT),
+            storage_texture_layout_entry(NORMAL_DEPTH_TEXTURE_BIND, NORMAL_DEPTH_FORMAT),
+            wgpu::BindGroupLayoutEntry {
+                binding: ALBEDO_ACCUMULATION_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: NORMAL_DEPTH_ACCUMULATION_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+        label: None,
+    })
+}
+
+pub fn texture_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    // a pixel texture a later pass loads from, the 32 bit float formats could not be filtered anyway
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+pub fn storage_texture_layout_entry(
+    binding: u32,
+    format: wgpu::TextureFormat,
+) -> wgpu::BindGroupLayoutEntry {
+    // a pixel texture the compute shader writes to
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::StorageTexture {
+            access: wgpu::StorageTextureAccess::WriteOnly,
+            format,
+            view_dimension: wgpu::TextureViewDimension::D2,
+        },
+        count: None,
+    }
+}
@@ -1,22 +1,160 @@
-use super::buffers::Ray;
+use super::buffers::{
+    PROJECTION_EQUIRECTANGULAR, PROJECTION_FISHEYE, PROJECTION_ORTHOGRAPHIC,
+    PROJECTION_PERSPECTIVE, RayCamera,
+};
+use super::camera_path::{CameraKeyframe, CameraPath};
 use egui::Context;
-use glam::{Mat4, Quat, Vec3A, Vec4, vec2, vec3a, vec4};
-use rayon::prelude::*;
+use glam::{Mat4, Quat, Vec2, Vec3, Vec3A, Vec4, vec2, vec3, vec4};
+use std::f32::consts::PI;
+use std::path::Path;
+use std::time::Instant;
+
+// seconds it takes to fly to a bookmark
+const TRANSITION_TIME: f32 = 1.0;
+
+// keeps the orbiting camera from flipping over the top and bottom of the center
+const MAX_ORBIT_PITCH: f32 = 0.99;
+
+// file in the scene folder that overrides the starting view and lens of the scene definition
+pub const CAMERA_SETTINGS_FILE: &str = "camera.toml";
+
+// starting view and lens of the camera, part of the scene definition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraSettings {
+    pub position: Vec3A,
+    pub direction: Vec3A,
+    pub vertical_fov: f32,
+    pub near_clip: f32,
+    pub far_clip: f32,
+    pub movement_speed: f32,
+    pub turning_speed: f32,
+}
+
+impl CameraSettings {
+    pub fn load(path: &Path, defaults: CameraSettings) -> CameraSettings {
+        // the file is optional, the values it leaves out keep the ones of the scene definition
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return defaults;
+        };
+
+        match defaults.parse(&text) {
+            Ok(settings) => settings,
+            Err(error) => {
+                println!(
+                    "{}: {}, the camera of the scene definition is used",
+                    path.display(),
+                    error
+                );
+                defaults
+            }
+        }
+    }
+
+    fn parse(self, text: &str) -> Result<CameraSettings, String> {
+        // one "key = value" per line like in TOML, vectors are written as [x, y, z]
+        let mut settings = self;
+
+        for (line_index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let line_error = |message: String| format!("line {}: {}", line_index + 1, message);
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| line_error(format!("{} has no value", line)))?;
+            let value = value.trim();
+
+            let number = || {
+                value
+                    .parse::<f32>()
+                    .map_err(|_| line_error(format!("{} is not a number", value)))
+            };
+            let vector = || {
+                let components: Option<Vec<f32>> = value
+                    .strip_prefix('[')
+                    .and_then(|value| value.strip_suffix(']'))
+                    .and_then(|value| {
+                        value
+                            .split(',')
+                            .map(|component| component.trim().parse().ok())
+                            .collect()
+                    });
+
+                match components.as_deref() {
+                    Some(&[x, y, z]) => Ok(Vec3A::new(x, y, z)),
+                    _ => Err(line_error(format!("{} is not a vector", value))),
+                }
+            };
+
+            match key.trim() {
+                "position" => settings.position = vector()?,
+                "direction" => settings.direction = vector()?.normalize_or(settings.direction),
+                "vertical_fov" => settings.vertical_fov = number()?,
+                "near_clip" => settings.near_clip = number()?,
+                "far_clip" => settings.far_clip = number()?,
+                "movement_speed" => settings.movement_speed = number()?,
+                "turning_speed" => settings.turning_speed = number()?,
+                key => return Err(line_error(format!("unknown setting {}", key))),
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+// saved viewpoint of the scene
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub position: Vec3A,
+    pub direction: Vec3A,
+    pub vertical_fov: f32,
+    pub aperture_radius: f32,
+    pub focus_distance: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CameraTransition {
+    from: CameraBookmark,
+    to: CameraBookmark,
+    start_time: Instant,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Camera {
     pub position: Vec3A,
     pub direction: Vec3A,
 
-    near_clip: f32,
-    far_clip: f32,
-    vertical_fov: f32,
+    pub near_clip: f32,
+    pub far_clip: f32,
+    pub vertical_fov: f32,
 
     pub viewport_width: u32,
     pub viewport_height: u32,
 
     pub movement_speed: f32,
-    turning_speed: f32,
+    pub turning_speed: f32,
+
+    // thin lens, an aperture radius of 0 is a pinhole camera
+    pub aperture_radius: f32,
+    pub focus_distance: f32,
+    pub aperture_blades: u32,
+
+    pub projection_type: u32,
+    pub orthographic_height: f32,
+    pub fisheye_fov: f32,
+
+    // position and direction at the end of the shutter, none when the camera does not move
+    pub motion_end: Option<(Vec3A, Vec3A)>,
+
+    // point the camera rotates around in orbit mode
+    pub orbit_center: Vec3A,
+
+    transition: Option<CameraTransition>,
 
     projection: Mat4,
     inverse_projection: Mat4,
@@ -25,20 +163,34 @@ pub struct Camera {
 }
 
 impl Camera {
-    pub fn new(width: u32, height: u32) -> Camera {
+    pub fn new(width: u32, height: u32, settings: &CameraSettings) -> Camera {
         let mut camera = Camera {
-            position: vec3a(0.0, -6.0, 25.),
-            direction: vec3a(0., 0., -1.),
+            position: settings.position,
+            direction: settings.direction.normalize(),
 
             viewport_width: width,
             viewport_height: height,
 
-            near_clip: 0.1,
-            far_clip: 100.0,
-            vertical_fov: 45.0,
+            near_clip: settings.near_clip,
+            far_clip: settings.far_clip,
+            vertical_fov: settings.vertical_fov,
+
+            movement_speed: settings.movement_speed,
+            turning_speed: settings.turning_speed,
+
+            aperture_radius: 0.0,
+            focus_distance: 25.0,
+            aperture_blades: 0,
 
-            movement_speed: 0.5,
-            turning_speed: 0.001,
+            projection_type: PROJECTION_PERSPECTIVE,
+            orthographic_height: 20.0,
+            fisheye_fov: 180.0,
+
+            motion_end: None,
+
+            orbit_center: Vec3A::ZERO,
+
+            transition: None,
 
             projection: Mat4::from_cols_slice(&[1.0; 16]),
             inverse_projection: Mat4::from_cols_slice(&[1.0; 16]),
@@ -48,7 +200,6 @@ impl Camera {
 
         camera.recalculate_view();
         camera.recalculate_projection();
-        camera.recalculate_ray_directions();
 
         camera
     }
@@ -113,14 +264,156 @@ impl Camera {
         }
 
         if moved {
+            // flying by hand stops the transition to a bookmark
+            self.transition = None;
             self.recalculate_view();
         }
         moved
     }
 
-    fn recalculate_projection(&mut self) {
+    pub fn look_at(&mut self, center: Vec3A) {
+        self.orbit_center = center;
+        self.direction = (center - self.position).normalize_or(self.direction);
+        self.transition = None;
+
+        self.recalculate_view();
+    }
+
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        // rotates the camera around the orbit center, angles in radians
+        let offset = self.position - self.orbit_center;
+        let right_direction = self.direction.cross(Vec3A::Y).normalize_or(Vec3A::X);
+
+        let yaw_rotation = Quat::from_axis_angle(Vec3::Y, -yaw);
+        let pitch_rotation = Quat::from_axis_angle(right_direction.into(), pitch);
+
+        let mut new_offset = yaw_rotation * offset;
+        let pitched_offset = yaw_rotation * pitch_rotation * offset;
+
+        if pitched_offset.normalize_or_zero().dot(Vec3A::Y).abs() < MAX_ORBIT_PITCH {
+            new_offset = pitched_offset;
+        }
+
+        self.position = self.orbit_center + new_offset;
+        self.look_at(self.orbit_center);
+    }
+
+    pub fn zoom(&mut self, scroll: f32) {
+        // moves the camera towards the orbit center, every scroll step is 10% closer
+        let distance = (self.position - self.orbit_center).length();
+        let new_distance =
+            (distance * 0.9_f32.powf(scroll)).clamp(self.near_clip * 2.0, self.far_clip);
+
+        self.position = self.orbit_center - self.direction * new_distance;
+        self.transition = None;
+
+        self.recalculate_view();
+    }
+
+    pub fn pan(&mut self, mouse_delta: egui::Vec2) {
+        // moves the camera and orbit center so the point under the cursor follows the mouse
+        // height of the view at the orbit center, the orthographic view has the same height at every distance
+        let view_height = match self.projection_type {
+            PROJECTION_ORTHOGRAPHIC => self.orthographic_height,
+            _ => {
+                let distance = (self.position - self.orbit_center).length();
+                2.0 * distance * (self.vertical_fov.to_radians() * 0.5).tan()
+            }
+        };
+        let pixel_size = view_height / self.viewport_height as f32;
+
+        let right_direction = self.direction.cross(Vec3A::Y).normalize_or(Vec3A::X);
+        let up_direction = right_direction.cross(self.direction);
+
+        // screen y points down, which is up in the view space of the camera
+        let shift = (-right_direction * mouse_delta.x - up_direction * mouse_delta.y) * pixel_size;
+
+        self.position += shift;
+        self.orbit_center += shift;
+        self.transition = None;
+
+        self.recalculate_view();
+    }
+
+    pub fn bookmark(&self, name: String) -> CameraBookmark {
+        CameraBookmark {
+            name,
+            position: self.position,
+            direction: self.direction,
+            vertical_fov: self.vertical_fov,
+            aperture_radius: self.aperture_radius,
+            focus_distance: self.focus_distance,
+        }
+    }
+
+    pub fn start_transition(&mut self, bookmark: &CameraBookmark) {
+        self.transition = Some(CameraTransition {
+            from: self.bookmark(String::new()),
+            to: bookmark.clone(),
+            start_time: Instant::now(),
+        });
+    }
+
+    pub fn update_transition(&mut self) -> bool {
+        // moves the camera along the transition, returns true while the camera is moving
+        let Some(transition) = &self.transition else {
+            return false;
+        };
+
+        let progress = (transition.start_time.elapsed().as_secs_f32() / TRANSITION_TIME).min(1.0);
+
+        // smoothstep so the camera eases in and out of the movement
+        let t = progress * progress * (3.0 - 2.0 * progress);
+
+        let from = &transition.from;
+        let to = &transition.to;
+
+        let rotation = Quat::from_rotation_arc(from.direction.into(), to.direction.into());
+
+        self.position = from.position.lerp(to.position, t);
+        self.direction = Quat::IDENTITY.slerp(rotation, t) * from.direction;
+        self.vertical_fov = from.vertical_fov + (to.vertical_fov - from.vertical_fov) * t;
+        self.aperture_radius =
+            from.aperture_radius + (to.aperture_radius - from.aperture_radius) * t;
+        self.focus_distance = from.focus_distance + (to.focus_distance - from.focus_distance) * t;
+
+        if progress >= 1.0 {
+            self.transition = None;
+        }
+
+        self.recalculate_view();
+        self.recalculate_projection();
+        true
+    }
+
+    pub fn keyframe(&self, time: f32) -> CameraKeyframe {
+        CameraKeyframe {
+            time,
+            position: self.position,
+            direction: self.direction,
+            vertical_fov: self.vertical_fov,
+        }
+    }
+
+    pub fn follow_path(&mut self, path: &CameraPath, time: f32) -> bool {
+        // places the camera where the path is at the given time, returns false for an empty path
+        let Some(keyframe) = path.evaluate(time) else {
+            return false;
+        };
+
+        self.position = keyframe.position;
+        self.direction = keyframe.direction;
+        self.vertical_fov = keyframe.vertical_fov;
+        self.transition = None;
+
+        self.recalculate_view();
+        self.recalculate_projection();
+        true
+    }
+
+    pub fn recalculate_projection(&mut self) {
         let fov_rad: f32 = self.vertical_fov.to_radians();
-        let aspect_ratio = (self.viewport_width / self.viewport_height) as f32;
+        let aspect_ratio = self.viewport_width as f32 / self.viewport_height as f32;
         self.projection =
             Mat4::perspective_rh_gl(fov_rad, aspect_ratio, self.near_clip, self.far_clip);
 
@@ -136,49 +429,81 @@ impl Camera {
         self.inverse_view = self.view.inverse();
     }
 
-    pub fn recalculate_ray_directions(&self) -> Vec<Ray> {
-        // multithreadded implementation
-
+    pub fn primary_ray(&self, normalized_coord: Vec2) -> Option<(Vec3A, Vec3A)> {
+        // origin and direction of the pinhole ray through a screen position between -1 and 1,
+        // same as the projections in the compute shader
         let aspect_ratio = self.viewport_width as f32 / self.viewport_height as f32;
 
-        // Create new ray directions in parallel
-        let new_ray_directions: Vec<Ray> = (0..self.viewport_height)
-            .into_par_iter()
-            .flat_map(|y| {
-                let y_coord: f32 = y as f32 / self.viewport_height as f32;
-                (0..self.viewport_width)
-                    .map(|x| {
-                        let x_coord = x as f32 / self.viewport_width as f32;
-
-                        // normalized between -1 and 1
-                        let normalized_coord = vec2(x_coord, y_coord) * 2.0 - 1.0;
+        let (view_origin, view_direction) = match self.projection_type {
+            PROJECTION_ORTHOGRAPHIC => {
+                let half_size = vec2(aspect_ratio, 1.0) * self.orthographic_height * 0.5;
+                ((normalized_coord * half_size).extend(0.0), Vec3::NEG_Z)
+            }
+            PROJECTION_EQUIRECTANGULAR => {
+                let phi = normalized_coord.x * PI;
+                let theta = normalized_coord.y * PI * 0.5;
+                let direction = vec3(
+                    phi.sin() * theta.cos(),
+                    theta.sin(),
+                    -phi.cos() * theta.cos(),
+                );
+                (Vec3::ZERO, direction)
+            }
+            PROJECTION_FISHEYE => {
+                let position = vec2(normalized_coord.x * aspect_ratio, normalized_coord.y);
+                let radius = position.length();
 
-                        // rescale for aspect ratio
-                        let adjusted_coord =
-                            vec2(normalized_coord.x * aspect_ratio, normalized_coord.y);
+                if radius > 1.0 {
+                    return None;
+                }
 
-                        let target: Vec4 = self.inverse_projection
-                            * vec4(adjusted_coord.x, adjusted_coord.y, 1.0, 1.0);
+                let theta = radius * self.fisheye_fov.to_radians() * 0.5;
+                let side = position.normalize_or_zero() * theta.sin();
+                (Vec3::ZERO, side.extend(-theta.cos()))
+            }
+            _ => {
+                let target: Vec4 = self.inverse_projection
+                    * vec4(normalized_coord.x, normalized_coord.y, 1.0, 1.0);
+                (Vec3::ZERO, (target.truncate() / target.w).normalize())
+            }
+        };
 
-                        let target_vec3: Vec3A = target.truncate().into();
+        let origin = self.inverse_view.transform_point3(view_origin).into();
+        let direction = self
+            .inverse_view
+            .transform_vector3(view_direction)
+            .normalize()
+            .into();
 
-                        let world_space_target: Vec4 =
-                            (target_vec3 / target.w).normalize().extend(0.0);
+        Some((origin, direction))
+    }
 
-                        let ray_direction: Vec3A =
-                            (self.inverse_view * world_space_target).truncate().into();
+    pub fn get_ray_camera(&self) -> RayCamera {
+        let (end_position, end_direction) =
+            self.motion_end.unwrap_or((self.position, self.direction));
 
-                        // caching the ray directions so we dont need to calculate them every frame
-                        Ray {
-                            direction: ray_direction.into(),
-                            _padding: [0; 4],
-                        }
-                    })
-                    .collect::<Vec<Ray>>()
-            })
-            .collect();
+        let end_view = Mat4::look_at_rh(
+            end_position.into(),
+            (end_position + end_direction).into(),
+            glam::Vec3::Y,
+        );
 
-        new_ray_directions
+        RayCamera {
+            inverse_projection: self.inverse_projection.to_cols_array_2d(),
+            inverse_view: self.inverse_view.to_cols_array_2d(),
+            end_inverse_view: end_view.inverse().to_cols_array_2d(),
+            origin: self.position.into(),
+            aperture_radius: self.aperture_radius,
+            focus_distance: self.focus_distance,
+            aperture_blades: self.aperture_blades,
+            projection_type: self.projection_type,
+            orthographic_height: self.orthographic_height,
+            end_origin: end_position.into(),
+            fisheye_fov: self.fisheye_fov,
+            near_clip: self.near_clip,
+            far_clip: self.far_clip,
+            _padding: [0; 8],
+        }
     }
 
     pub fn on_resize(&mut self, width: u32, height: u32) {
@@ -190,6 +515,5 @@ impl Camera {
         self.viewport_height = height;
 
         self.recalculate_projection();
-        self.recalculate_ray_directions();
     }
 }
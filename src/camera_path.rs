@@ -0,0 +1,87 @@
+use glam::Vec3A;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraKeyframe {
+    pub time: f32, // seconds from the start of the path
+    pub position: Vec3A,
+    pub direction: Vec3A,
+    pub vertical_fov: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraPath {
+    pub keyframes: Vec<CameraKeyframe>,
+    pub frames_per_second: f32,
+}
+
+impl CameraPath {
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        (self.duration() * self.frames_per_second).floor() as u32 + 1
+    }
+
+    pub fn add_keyframe(&mut self, keyframe: CameraKeyframe) {
+        // keyframes are kept in time order for the evaluation
+        let index = self
+            .keyframes
+            .partition_point(|other| other.time <= keyframe.time);
+
+        self.keyframes.insert(index, keyframe);
+    }
+
+    pub fn evaluate(&self, time: f32) -> Option<CameraKeyframe> {
+        // catmull rom spline through the keyframes, the ends of the path repeat the first and last keyframe
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+
+        if time <= first.time {
+            return Some(*first);
+        }
+        if time >= last.time {
+            return Some(*last);
+        }
+
+        let segment = self
+            .keyframes
+            .partition_point(|keyframe| keyframe.time <= time)
+            - 1;
+
+        let k1 = &self.keyframes[segment];
+        let k2 = &self.keyframes[segment + 1];
+        let k0 = &self.keyframes[segment.saturating_sub(1)];
+        let k3 = &self.keyframes[(segment + 2).min(self.keyframes.len() - 1)];
+
+        let t = (time - k1.time) / (k2.time - k1.time).max(f32::EPSILON);
+
+        let position = catmull_rom(k0.position, k1.position, k2.position, k3.position, t);
+        let direction = catmull_rom(k0.direction, k1.direction, k2.direction, k3.direction, t);
+
+        let fov = catmull_rom(
+            Vec3A::splat(k0.vertical_fov),
+            Vec3A::splat(k1.vertical_fov),
+            Vec3A::splat(k2.vertical_fov),
+            Vec3A::splat(k3.vertical_fov),
+            t,
+        );
+
+        Some(CameraKeyframe {
+            time,
+            position,
+            direction: direction.normalize_or(k1.direction),
+            vertical_fov: fov.x,
+        })
+    }
+}
+
+fn catmull_rom(p0: Vec3A, p1: Vec3A, p2: Vec3A, p3: Vec3A, t: f32) -> Vec3A {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
@@ -0,0 +1,206 @@
+use wgpu::Backends;
+
+use std::path::PathBuf;
+
+pub const USAGE: &str = "usage: gpu_ray_tracing [options]
+  --scene PATH          folder with the 3D_models, textures, env_maps and camera.toml of the scene (default .)
+  --width N             width of the window or image (default 1600)
+  --height N            height of the window or image (default 900)
+  --backend NAME        vulkan, dx12, metal, gl or all (default vulkan)
+  --samples N           samples per pixel, accumulation stops once they are reached
+  --output PATH         image saved when the samples are reached, .exr and .pfm are linear
+  --headless            render without a window and exit, needs no display
+  --fallback-adapter    use the software adapter, for machines without a gpu
+  --seed N              seed of the random sampling (default 0)
+  --denoise             filter the noise of the rendered image, also in saved images
+  --tile N              headless renders are split into tiles of N x N pixels (default: as large as the gpu allows)
+  --help                show this message";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub scene_path: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    pub backend: Backends,
+    pub samples: Option<u32>,
+    pub output: Option<String>,
+    pub headless: bool,
+    pub fallback_adapter: bool,
+    pub seed: u32,
+    pub tile_size: Option<u32>,
+    pub denoise: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            scene_path: PathBuf::from("."),
+            width: 1600,
+            height: 900,
+            backend: Backends::VULKAN,
+            samples: None,
+            output: None,
+            headless: false,
+            fallback_adapter: false,
+            seed: 0,
+            tile_size: None,
+            denoise: false,
+        }
+    }
+}
+
+pub fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+
+    let mut args = args;
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value after {}", arg))
+        };
+
+        match arg.as_str() {
+            "--scene" => options.scene_path = PathBuf::from(value()?),
+            "--width" => options.width = parse_number(&value()?)?,
+            "--height" => options.height = parse_number(&value()?)?,
+            "--backend" => options.backend = parse_backend(&value()?)?,
+            "--samples" => options.samples = Some(parse_number(&value()?)?),
+            "--output" => options.output = Some(value()?),
+            "--headless" => options.headless = true,
+            "--fallback-adapter" => options.fallback_adapter = true,
+            "--seed" => {
+                let seed = value()?;
+                options.seed = seed
+                    .parse()
+                    .map_err(|_| format!("{} is not a valid seed", seed))?;
+            }
+            "--denoise" => options.denoise = true,
+            "--tile" => options.tile_size = Some(parse_number(&value()?)?),
+            "--help" | "-h" => return Err(String::new()),
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_number(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(format!("{} is not a positive number", value)),
+    }
+}
+
+fn parse_backend(name: &str) -> Result<Backends, String> {
+    match name.to_lowercase().as_str() {
+        "vulkan" => Ok(Backends::VULKAN),
+        "dx12" => Ok(Backends::DX12),
+        "metal" => Ok(Backends::METAL),
+        "gl" => Ok(Backends::GL),
+        "all" => Ok(Backends::all()),
+        _ => Err(format!("unknown backend {}", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_give_the_defaults() {
+        assert_eq!(parse(&[]), Ok(Options::default()));
+    }
+
+    #[test]
+    fn parses_every_option() {
+        let options = parse(&[
+            "--scene",
+            "scenes/chess",
+            "--width",
+            "800",
+            "--height",
+            "600",
+            "--backend",
+            "Metal",
+            "--samples",
+            "64",
+            "--output",
+            "render.exr",
+            "--headless",
+            "--fallback-adapter",
+            "--seed",
+            "7",
+            "--denoise",
+            "--tile",
+            "256",
+        ]);
+
+        assert_eq!(
+            options,
+            Ok(Options {
+                scene_path: PathBuf::from("scenes/chess"),
+                width: 800,
+                height: 600,
+                backend: Backends::METAL,
+                samples: Some(64),
+                output: Some("render.exr".to_string()),
+                headless: true,
+                fallback_adapter: true,
+                seed: 7,
+                tile_size: Some(256),
+                denoise: true,
+            })
+        );
+    }
+
+    #[test]
+    fn seed_can_be_zero() {
+        assert_eq!(parse(&["--seed", "0"]).map(|options| options.seed), Ok(0));
+    }
+
+    #[test]
+    fn rejects_an_unknown_backend() {
+        assert_eq!(
+            parse(&["--backend", "opengl"]),
+            Err("unknown backend opengl".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_a_missing_value() {
+        assert_eq!(
+            parse(&["--headless", "--width"]),
+            Err("missing value after --width".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_sizes_that_are_not_positive_numbers() {
+        assert_eq!(
+            parse(&["--width", "wide"]),
+            Err("wide is not a positive number".to_string())
+        );
+        assert_eq!(
+            parse(&["--height", "0"]),
+            Err("0 is not a positive number".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_an_unknown_argument() {
+        assert_eq!(
+            parse(&["--fast"]),
+            Err("unknown argument --fast".to_string())
+        );
+    }
+
+    #[test]
+    fn help_is_an_empty_error() {
+        assert_eq!(parse(&["--help"]), Err(String::new()));
+    }
+}
@@ -1,6 +1,34 @@
 const F32_MAX: f32 = 3.4028235e+38;
 const U32_MAX: u32 = 4294967295u;
 const PI: f32 = 3.1415926536;
+const NO_TEXTURE: u32 = U32_MAX;
+
+// primitive types for hits and emitters
+const PRIMITIVE_SPHERE: u32 = 0u;
+const PRIMITIVE_TRIANGLE: u32 = 1u;
+
+// analytic light types
+const LIGHT_POINT: u32 = 0u;
+const LIGHT_SPOT: u32 = 1u;
+const LIGHT_DIRECTIONAL: u32 = 2u;
+const LIGHT_RECTANGLE: u32 = 3u;
+const LIGHT_DISK: u32 = 4u;
+
+// camera projections
+const PROJECTION_PERSPECTIVE: u32 = 0u;
+const PROJECTION_ORTHOGRAPHIC: u32 = 1u;
+const PROJECTION_EQUIRECTANGULAR: u32 = 2u;
+const PROJECTION_FISHEYE: u32 = 3u;
+
+// pixel reconstruction filters
+const FILTER_BOX: u32 = 0u;
+const FILTER_TENT: u32 = 1u;
+const FILTER_GAUSSIAN: u32 = 2u;
+const FILTER_BLACKMAN_HARRIS: u32 = 3u;
+
+// random sequence dimensions reserved for the camera ray and for every bounce
+const CAMERA_DIMENSIONS: u32 = 6u;
+const DIMENSIONS_PER_BOUNCE: u32 = 32u;
 
 
 const TRIANGLE_COUNT: u32 = TRIANGLE_COUNT_PLACEHOLDER; 
@@ -8,10 +36,9 @@ const SUB_OBJECT_COUNT: u32 = SUBOBJECT_COUNT_PLACEHOLDER;
 const OBJECT_COUNT: u32 = OBJECT_COUNT_PLACEHOLDER;
 const SPERE_COUNT: u32 = SPHERE_COUNT_PLACEHOLDER;
 const MATERIAL_COUNT: u32 = MATERIAL_COUNT_PLACEHOLDER;
+const LIGHT_COUNT: u32 = LIGHT_COUNT_PLACEHOLDER;
 
 @group(0) @binding(0) var<storage, read> params: Params;
-@group(0) @binding(1) var<storage, read> camera_rays: array<vec3<f32>>;
-@group(0) @binding(2) var<storage, read_write> output_data: array<u32>;
 @group(0) @binding(3) var<uniform> ray_camera: RayCamera;
 @group(0) @binding(4) var<uniform> material_array: array<SceneMaterial, MATERIAL_COUNT>;
 @group(0) @binding(5) var<uniform> sphere_array: array<SceneSphere, SPERE_COUNT>;
@@ -21,6 +48,13 @@ const MATERIAL_COUNT: u32 = MATERIAL_COUNT_PLACEHOLDER;
 @group(0) @binding(9) var texture_array: texture_2d_array<f32>;
 @group(0) @binding(10) var<storage, read> sub_object_array: array<SubObjectInfo, SUB_OBJECT_COUNT>;
 @group(0) @binding(11) var environment_map: texture_2d<f32>;
+@group(0) @binding(12) var<storage, read> emitter_array: array<SceneEmitter>;
+@group(0) @binding(13) var<uniform> light_array: array<SceneLight, LIGHT_COUNT>;
+@group(0) @binding(14) var radiance_texture: texture_storage_2d<rgba32float, write>;
+@group(0) @binding(15) var albedo_texture: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(16) var normal_depth_texture: texture_storage_2d<rgba32float, write>;
+@group(0) @binding(17) var<storage, read_write> albedo_accumulation: array<vec4<f32>>;
+@group(0) @binding(18) var<storage, read_write> normal_depth_accumulation: array<vec4<f32>>;
 
 
 fn sample_texture(index: u32, coords: vec2<f32>, texture_size: vec2<i32>) -> vec4<f32> {
@@ -42,7 +76,7 @@ fn sample_env_map(coords: vec2<f32>, texture_size: vec2<i32>) -> vec4<f32> {
 
 struct Params {
     width: u32,
-    accumulation_index: u32,
+    accumulated_samples: u32,
     accumulate: u32,
     sphere_count: u32,   
     object_count: u32, 
@@ -52,17 +86,41 @@ struct Params {
     textue_count: u32,
     env_map_width: u32,
     env_map_height: u32,
-    // explicit padding to match 16 byte alignment
-    _padding1: u32,
-      
+    emitter_count: u32,
+    light_count: u32,
+    max_diffuse_bounces: u32,
+    max_specular_bounces: u32,
+    max_transmission_bounces: u32,
+    roulette_depth: u32,
+    height: u32,
+    filter_type: u32,
+    filter_radius: f32,
+    motion_blur: u32,
+    seed: u32,
+    tile_x: u32,
+    tile_y: u32,
+    tile_width: u32,
+    tile_height: u32,
 };
 
 
 struct RayCamera {
+    inverse_projection: mat4x4<f32>,
+    inverse_view: mat4x4<f32>,
+    end_inverse_view: mat4x4<f32>,
     origin: vec3<f32>,    
-
+    aperture_radius: f32,
+    focus_distance: f32,
+    aperture_blades: u32,
+    projection_type: u32,
+    orthographic_height: f32,
+    end_origin: vec3<f32>,
+    fisheye_fov: f32,
+    near_clip: f32,
+    far_clip: f32,
     // explicit padding to match 16 byte alignment
-     _padding1: u32,
+    _padding1: u32,
+    _padding2: u32,
 };
 
 struct SceneMaterial {
@@ -73,8 +131,16 @@ struct SceneMaterial {
     specular_scatter: f32,
     glass: f32,
     refraction_index: f32,
+    normal_map_index: u32,
+    height_map_index: u32,
+    normal_strength: f32,
+    roughness_map_index: u32,
+    specular_map_index: u32,
+    emission_map_index: u32,
     // explicit padding to match 16 byte alignment
     _padding1: u32,
+    _padding2: u32,
+    _padding3: u32,
            
 }
 
@@ -104,6 +170,8 @@ struct SceneTriangle {
     _padding6: u32,
     max_bounds: vec3<f32>,
     _padding7: u32,
+    tangent: vec3<f32>,
+    tangent_sign: f32,
     // explicit padding to match 16 byte alignment 
 }
 
@@ -113,9 +181,39 @@ struct ObjectInfo {
     max_bounds: vec3<f32>,
     sub_object_count: u32,
     material_index: u32,
+    moving: u32,
     _padding1: u32,
     _padding2: u32,
-    _padding3: u32,
+    // bounds of everything the object covers while the shutter is open
+    swept_min_bounds: vec3<f32>,
+    swept_max_bounds: vec3<f32>,
+    // start transform and its change until the end of the shutter, rotations in degrees
+    translation: vec3<f32>,
+    rotation: vec3<f32>,
+    motion_translation: vec3<f32>,
+    motion_rotation: vec3<f32>,
+}
+
+struct SceneEmitter {
+    primitive_type: u32,
+    primitive_index: u32,
+    // object of an emissive triangle, its motion moves the sampled point
+    object_index: u32,
+    // explicit padding to match 16 byte alignment
+    _padding: u32,
+}
+
+struct SceneLight {
+    position: vec3<f32>,
+    light_type: u32,
+    direction: vec3<f32>,
+    intensity: f32,
+    color: vec3<f32>,
+    spot_angle: f32,
+    size: vec2<f32>,
+    spot_softness: f32,
+    // explicit padding to match 16 byte alignment
+    _padding1: u32,
 }
 
 struct SubObjectInfo {
@@ -133,7 +231,11 @@ struct HitPayload {
     hitside_normal: vec3<f32>,
     material_index: u32,
     front_face: bool,
-    texture_point: vec2<f32>
+    texture_point: vec2<f32>,
+    // xyz is the surface tangent, w is the handedness of the bitangent
+    tangent: vec4<f32>,
+    primitive_type: u32,
+    primitive_index: u32,
 }
 
 struct Ray {
@@ -141,91 +243,158 @@ struct Ray {
     direction: vec3<f32>,
 }
 
+// moment in the shutter interval of the current path, 0 is the start and 1 the end
+var<private> path_time: f32;
+
+// surface seen by the camera ray, the denoiser uses it to find the edges of the image
+var<private> guide_albedo: vec3<f32>;
+var<private> guide_normal: vec3<f32>;
+var<private> guide_depth: f32;
+
+struct RandomState {
+    pixel_seed: u32,
+    sample_index: u32,
+    dimension: u32,
+}
+
 
 
 @compute @workgroup_size(8, 8, 1)
 fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
-    let index: u32 =  (global_id.y * params.width) + global_id.x;
+    // the last workgroups can reach past the edge of the tile
+    if global_id.x >= params.tile_width || global_id.y >= params.tile_height {
+        return;
+    }
 
-    let bounces: u32 = 10u;
+    // the accumulation buffer and the pixel textures hold one tile, the rays are traced for the pixel in the whole image
+    let index: u32 = (global_id.y * params.tile_width) + global_id.x;
+    let image_pixel: vec2<u32> = vec2<u32>(params.tile_x, params.tile_y) + global_id.xy;
+    let pixel_index: u32 = (image_pixel.y * params.width) + image_pixel.x;
 
-    var render_color = vec4<f32>(0.0);
+    var radiance = vec4<f32>(0.0);
+    var albedo = vec3<f32>(0.0);
+    var normal_depth = vec4<f32>(0.0);
 
-    var random_index = params.accumulation_index;
+    // every sample of a pixel gets its own index into the low discrepancy sequence
+    var sample_index = params.accumulated_samples;
 
     var pixel_color: vec4<f32> = accumulation_data[index];
+    var albedo_sum: vec4<f32> = albedo_accumulation[index];
+    var normal_depth_sum: vec4<f32> = normal_depth_accumulation[index];
 
     if params.accumulate == 1{
 
+        // the guides are summed like the radiance, a single jittered sample of an edge would be noise to the denoiser
         for (var i: u32 = 0u; i < params.compute_per_frame; i = i + 1) {
-            pixel_color += per_pixel(index, bounces, random_index);
-            random_index = random_index + 1;
+            pixel_color += per_pixel(pixel_index, sample_index);
+            albedo_sum += vec4<f32>(guide_albedo, 1.0);
+            normal_depth_sum += vec4<f32>(guide_normal, guide_depth);
+            sample_index = sample_index + 1u;
         }
         accumulation_data[index] = pixel_color;
+        albedo_accumulation[index] = albedo_sum;
+        normal_depth_accumulation[index] = normal_depth_sum;
 
-        var accumulated_color: vec4<f32> = pixel_color / f32(params.accumulation_index * params.compute_per_frame);
+        let sample_count: f32 = f32(params.accumulated_samples + params.compute_per_frame);
+        radiance = pixel_color / sample_count;
+        albedo = albedo_sum.rgb / sample_count;
+        normal_depth = normal_depth_sum / sample_count;
 
-        render_color = clamp(accumulated_color, vec4<f32>(0.0), vec4<f32>(1.0));
-        
+        // the average of the normals at an edge is shorter than 1, the sky keeps its zero normal
+        if length(normal_depth.xyz) > 0.0 {
+            normal_depth = vec4<f32>(normalize(normal_depth.xyz), normal_depth.w);
+        }
 
     }else{
 
-        let f32_color: vec4<f32> = per_pixel(index, bounces, random_index);
-        render_color = clamp(f32_color, vec4<f32>(0.0), vec4<f32>(1.0));
+        radiance = per_pixel(pixel_index, sample_index);
+        albedo = guide_albedo;
+        normal_depth = vec4<f32>(guide_normal, guide_depth);
     }
-    
-    // pack 4 f32 values into a single u32 (4x u8 rgba color)
-    output_data[index] = pack_to_u32(render_color);
-
 
-    /*let count: u32 = object_array[0].triangle_count;
+    let texture_coords = vec2<i32>(global_id.xy);
 
-    if (count == 202) {
-        output_data[index] = pack_to_u32(vec3<f32>(0.0, 1.0, 0.0)); // green
-    } else {
-        output_data[index] = pack_to_u32(vec3<f32>(1.0, 0.0, 0.0)); // red
-    };*/
+    // the display pass tone maps the radiance, the guides are inputs of the denoiser
+    textureStore(radiance_texture, texture_coords, radiance);
+    textureStore(albedo_texture, texture_coords, vec4<f32>(albedo, 1.0));
+    textureStore(normal_depth_texture, texture_coords, normal_depth);
 
 }
 
 
-fn pack_to_u32(vector: vec4<f32>) -> u32 {
-  // scale the f32 values from [0.0, 1.0] to [0.0, 255.0]
-    let scaled_r: u32 = u32(vector.r * 255.0);
-    let scaled_g: u32 = u32(vector.g * 255.0);
-    let scaled_b: u32 = u32(vector.b * 255.0);
-    let scaled_a: u32 = u32(vector.a * 255.0);
+fn per_pixel(index: u32, sample_index: u32) -> vec4<f32> {
 
+    // the seed changes the scrambling of every pixel, so renders with different seeds have independent noise
+    var rng = RandomState(hash_combine(hash_u32(index), params.seed), sample_index, 0u);
 
-    // extract the least significant 8 bits (same as converting to u8)
-    let byte0: u32 = scaled_r & 0xFFu;
-    let byte1: u32 = scaled_g & 0xFFu;
-    let byte2: u32 = scaled_b & 0xFFu;
-    let byte3: u32 = scaled_a & 0xFFu;
+    // the first two dimensions place the sample inside the pixel footprint of the filter
+    let pixel = vec2<f32>(f32(index % params.width), f32(index / params.width));
+    let pixel_offset: vec2<f32> = sample_filter(&rng);
 
-    // pack the bits into a single u32 that will then be read as 4x u8 by the rendering pass
-    return (byte0 << 0) | (byte1 << 8) | (byte2 << 16) | (byte3 << 24);
-}
+    // the next two dimensions pick the point on the lens
+    let lens_sample = vec2<f32>(random(&rng), random(&rng));
+
+    // the whole path is traced at one moment while the shutter is open
+    path_time = 0.0;
+    if params.motion_blur == 1u {
+        path_time = random(&rng);
+    }
 
-fn per_pixel(index: u32, bounces: u32, random_index: u32) -> vec4<f32> {
+    let normalized_coord: vec2<f32> = (pixel + 0.5 + pixel_offset) / vec2<f32>(f32(params.width), f32(params.height)) * 2.0 - 1.0;
 
-    var ray = Ray( 
-        ray_camera.origin,
-        camera_rays[index]
-    );
+    // the sky and the area outside of the projection have no surface, the albedo of 1 keeps their color as it is
+    guide_albedo = vec3<f32>(1.0);
+    guide_normal = vec3<f32>(0.0);
+    guide_depth = ray_camera.far_clip;
+
+    if !inside_projection(normalized_coord) {
+        // outside of the fisheye circle
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
 
-    var seed: u32 = index * random_index * 326624u;
+    var ray = primary_ray(normalized_coord, lens_sample);
 
-    ray.direction += random_scaler(&seed) * 0.0005;
+    // the camera does not see anything closer than the near clip distance
+    ray.origin += ray.direction * ray_camera.near_clip;
     
     var light_contribution = vec4<f32>(1.0);
     var light = vec4<f32>(0.0);
 
-    
+    // chance of the lambertian lobe when the previous bounce was sampled from it, zero otherwise,
+    // only emitters hit through that lobe were also sampled directly and are weighted against the light sampling
+    var previous_diffuse_probability: f32 = 0.0;
+    var previous_normal = vec3<f32>(0.0);
+
+    // every type of bounce has its own limit, the path ends when the next bounce would go over it
+    var diffuse_bounces: u32 = 0u;
+    var specular_bounces: u32 = 0u;
+    var transmission_bounces: u32 = 0u;
+
+    let bounces: u32 = params.max_diffuse_bounces + params.max_specular_bounces + params.max_transmission_bounces;
+
+    for (var i: u32 = 0u; i <= bounces; i = i + 1) {
+
+        // every bounce starts at a fixed dimension, so the same decision of different samples
+        // draws from the same stratified sequence
+        rng.dimension = CAMERA_DIMENSIONS + i * DIMENSIONS_PER_BOUNCE;
+
+        var hit_payload: HitPayload = trace_ray(ray);
+
+        // nothing past the far clip distance is visible either, the ray already starts at the near clip distance
+        if i == 0u && hit_payload.hit_distance > ray_camera.far_clip - ray_camera.near_clip {
+            hit_payload = miss();
+        }
+
+        // area lights do not block the ray, the light of those in front of the hit is added on the way
+        if params.light_count > 0 {
+            var light_distance: f32 = hit_payload.hit_distance;
 
-    for (var i: u32 = 0u; i < bounces; i = i + 1) {
+            if i == 0u {
+                light_distance = min(light_distance, ray_camera.far_clip - ray_camera.near_clip);
+            }
 
-        let hit_payload: HitPayload = trace_ray(ray);
+            light += hit_area_lights(ray, light_distance, previous_diffuse_probability, previous_normal) * light_contribution;
+        }
 
         if hit_payload.hit_distance == F32_MAX {
             
@@ -240,20 +409,41 @@ fn per_pixel(index: u32, bounces: u32, random_index: u32) -> vec4<f32> {
             break;
         }
 
+        let texture_size = vec2<i32>(i32(params.texture_width), i32(params.texture_height));
+
         let material_index: u32 = hit_payload.material_index;
-        let current_material: SceneMaterial = material_array[material_index];
+        let current_material: SceneMaterial = sample_material_maps(material_array[material_index], hit_payload.texture_point, texture_size);
 
-        let diffuse_direction: vec3<f32> = normalize(hit_payload.hitside_normal + random_normal_scaler(&seed));
-        let specular_direction: vec3<f32> = reflect(ray.direction, hit_payload.hitside_normal);
-   
-        let texture_size = vec2<i32>(i32(params.texture_width), i32(params.texture_height));
+        // normal perturbed by the normal and height maps, used for shading but not for offsetting the ray origin
+        let shading_normal: vec3<f32> = perturb_normal(current_material, hit_payload, texture_size);
+
+        let current_color: vec4<f32> = srgb_to_linear(sample_texture(current_material.texture_index, hit_payload.texture_point, texture_size));
+
+        if i == 0u {
+            guide_albedo = current_color.rgb;
+            guide_normal = shading_normal;
+            guide_depth = hit_payload.hit_distance;
+        }
+
+        let diffuse_direction: vec3<f32> = cosine_direction(shading_normal, &rng);
+        let specular_direction: vec3<f32> = reflect(ray.direction, shading_normal);
 
-        let current_color: vec4<f32> = sample_texture(current_material.texture_index, hit_payload.texture_point, texture_size);
+        let emitted_light: vec4<f32> = material_emission(current_material, current_color, hit_payload.texture_point, texture_size);
 
-        let emitted_light = current_color * current_material.emission_power;
-        light += emitted_light * light_contribution;
+        var emission_weight: f32 = 1.0;
 
-        let is_glass: bool = current_material.glass > random(&seed);
+        if previous_diffuse_probability > 0.0 && current_material.emission_power > 0.0 {
+            let light_pdf: f32 = emitter_pdf(hit_payload, ray.origin);
+            let bsdf_pdf: f32 = previous_diffuse_probability * max(dot(previous_normal, normalize(ray.direction)), 0.0) / PI;
+
+            emission_weight = power_heuristic(bsdf_pdf, light_pdf);
+        }
+
+        light += emitted_light * light_contribution * emission_weight;
+
+        var sampled_diffuse_probability: f32 = 0.0;
+
+        let is_glass: bool = current_material.glass > random(&rng);
 
         if is_glass{
 
@@ -263,7 +453,7 @@ fn per_pixel(index: u32, bounces: u32, random_index: u32) -> vec4<f32> {
                 refraction_index = 1.0 /refraction_index;
             }
 
-            let cos_theta: f32 = min(dot(-ray.direction, hit_payload.hitside_normal), 1.0);
+            let cos_theta: f32 = min(dot(-ray.direction, shading_normal), 1.0);
 
             let sin_theta: f32 = sqrt(1.0 - cos_theta * cos_theta);
 
@@ -271,10 +461,15 @@ fn per_pixel(index: u32, bounces: u32, random_index: u32) -> vec4<f32> {
 
             let specular_percentage: f32 = specular_percentage(cos_theta, refraction_index);
 
-            let is_specular: bool = (current_material.specular * specular_percentage) > random(&seed);
+            let is_specular: bool = (current_material.specular * specular_percentage) > random(&rng);
 
             if reflects || is_specular {
                 // specular reflection, bounces off the glass
+
+                if specular_bounces >= params.max_specular_bounces {
+                    break;
+                }
+                specular_bounces += 1u;
                 
                 ray.direction = lerp(specular_direction, diffuse_direction, current_material.specular_scatter);
                 ray.origin = hit_payload.world_position + hit_payload.hitside_normal * 0.0001;
@@ -282,7 +477,12 @@ fn per_pixel(index: u32, bounces: u32, random_index: u32) -> vec4<f32> {
             } else { 
                 // refraction, goes through the glass
 
-                let refraction_direction: vec3<f32> = refract(ray.direction, hit_payload.hitside_normal, cos_theta, refraction_index);
+                if transmission_bounces >= params.max_transmission_bounces {
+                    break;
+                }
+                transmission_bounces += 1u;
+
+                let refraction_direction: vec3<f32> = refract(ray.direction, shading_normal, cos_theta, refraction_index);
                 
                 // normal roughness calculation in wayy to harsh for glass, 1/10 is plenty
                 ray.direction = lerp(refraction_direction, diffuse_direction, current_material.roughness / 10.0);
@@ -295,13 +495,59 @@ fn per_pixel(index: u32, bounces: u32, random_index: u32) -> vec4<f32> {
 
         }else{
 
-            let is_specular_bounce: bool = current_material.specular > random(&seed);
+            // the surface mixes a specular lobe, a lambertian lobe and a glossy lobe between the two,
+            // every lobe is picked with the share of the light it reflects, so the picks need no extra weight
+            let diffuse_probability: f32 = (1.0 - current_material.specular) * current_material.roughness;
+
+            // the lambertian lobe is lit directly by the emitters whether it is picked or not,
+            // as long as the path could still take a diffuse bounce and reach the same emitters that way
+            if diffuse_probability > 0.0 && diffuse_bounces < params.max_diffuse_bounces {
+                let shadow_origin: vec3<f32> = hit_payload.world_position + hit_payload.hitside_normal * 0.0001;
+                var direct_light = vec4<f32>(0.0);
+
+                if params.emitter_count > 0 {
+                    direct_light += sample_emitters(shadow_origin, shading_normal, diffuse_probability, &rng);
+                }
+
+                if params.light_count > 0 {
+                    direct_light += sample_lights(shadow_origin, shading_normal, diffuse_probability, &rng);
+                }
+
+                light += direct_light * current_color * light_contribution * diffuse_probability;
+            }
+
+            let lobe_choice: f32 = random(&rng);
+
+            if lobe_choice < current_material.specular {
+                if specular_bounces >= params.max_specular_bounces {
+                    break;
+                }
+                specular_bounces += 1u;
 
-            if is_specular_bounce{
                 ray.direction = lerp(specular_direction, diffuse_direction, current_material.specular_scatter);
 
-            }else{
+            } else if lobe_choice < current_material.specular + diffuse_probability {
+                if diffuse_bounces >= params.max_diffuse_bounces {
+                    break;
+                }
+                diffuse_bounces += 1u;
+
+                // lambertian lobe, the same one the emitters were sampled with
+                ray.direction = diffuse_direction;
+                sampled_diffuse_probability = diffuse_probability;
+
+                light_contribution *= current_color;
+
+            } else {
+                if diffuse_bounces >= params.max_diffuse_bounces {
+                    break;
+                }
+                diffuse_bounces += 1u;
+
+                // glossy lobe, it has no density to weigh against the light sampling,
+                // so emitters hit through it are counted fully and were not sampled for it directly
                 ray.direction = lerp(specular_direction, diffuse_direction, current_material.roughness);
+
                 light_contribution *= current_color;
             }
 
@@ -309,10 +555,623 @@ fn per_pixel(index: u32, bounces: u32, random_index: u32) -> vec4<f32> {
 
         }
 
+        previous_diffuse_probability = sampled_diffuse_probability;
+        previous_normal = shading_normal;
+
+        // russian roulette, paths that carry little light are ended early and the surviving ones are weighted up
+        if i + 1u >= params.roulette_depth {
+            let survival_chance: f32 = min(max(max(light_contribution.r, light_contribution.g), light_contribution.b), 0.95);
+
+            if random(&rng) >= survival_chance {
+                break;
+            }
+
+            light_contribution /= survival_chance;
+        }
+
+    }
+    return light;
+}
+
+fn material_emission(material: SceneMaterial, color: vec4<f32>, coords: vec2<f32>, texture_size: vec2<i32>) -> vec4<f32>{
+    var emission_color: vec4<f32> = color;
+
+    if material.emission_map_index != NO_TEXTURE {
+        emission_color = srgb_to_linear(sample_texture(material.emission_map_index, coords, texture_size));
+    }
+
+    return emission_color * material.emission_power;
+}
+
+fn sample_emitters(origin: vec3<f32>, normal: vec3<f32>, diffuse_probability: f32, rng: ptr<function, RandomState>) -> vec4<f32>{
+    // picks a random emissive sphere or triangle, samples a point on it and casts a shadow ray towards it,
+    // the lambertian lobe is picked with the diffuse probability, which scales its density for the weighting
+
+    let emitter_index: u32 = min(u32(random(rng) * f32(params.emitter_count)), params.emitter_count - 1u);
+    let emitter: SceneEmitter = emitter_array[emitter_index];
+
+    var direction: vec3<f32>;
+    var light_pdf: f32;
+
+    if emitter.primitive_type == PRIMITIVE_SPHERE {
+        let sphere: SceneSphere = sphere_array[emitter.primitive_index];
+
+        let to_center: vec3<f32> = sphere.position - origin;
+        let distance_squared: f32 = dot(to_center, to_center);
+        let radius_squared: f32 = sphere.radius * sphere.radius;
+
+        if distance_squared <= radius_squared {
+            // we are inside the sphere
+            return vec4<f32>(0.0);
+        }
+
+        // sample the cone of directions that the sphere covers
+        let cos_max: f32 = sqrt(1.0 - radius_squared / distance_squared);
+
+        direction = sample_cone(normalize(to_center), cos_max, rng);
+        light_pdf = 1.0 / (2.0 * PI * (1.0 - cos_max));
+
+    } else {
+        let tri: SceneTriangle = triangle_array[emitter.primitive_index];
+
+        // uniform point on the triangle
+        var u: f32 = random(rng);
+        var v: f32 = random(rng);
+
+        if u + v > 1.0 {
+            u = 1.0 - u;
+            v = 1.0 - v;
+        }
+
+        var light_point: vec3<f32> = tri.a + tri.edge_ab * u + tri.edge_ac * v;
+        var light_normal: vec3<f32> = tri.face_normal;
+
+        // the triangles are stored at the start of the shutter, moving objects are moved to the time of the path
+        let object_info: ObjectInfo = object_array[emitter.object_index];
+
+        if object_info.moving == 1u {
+            let motion: mat3x3<f32> = object_motion(object_info);
+            let current_translation: vec3<f32> = object_info.translation + object_info.motion_translation * path_time;
+
+            light_point = motion * (light_point - object_info.translation) + current_translation;
+            light_normal = motion * light_normal;
+        }
+
+        let to_light: vec3<f32> = light_point - origin;
+        let distance_squared: f32 = dot(to_light, to_light);
+
+        direction = normalize(to_light);
+
+        let light_cos: f32 = abs(dot(light_normal, direction));
+        let area: f32 = length(tri.calc_normal) * 0.5;
+
+        if light_cos < 0.000001 || area == 0.0 {
+            return vec4<f32>(0.0);
+        }
+
+        // area pdf converted to a solid angle pdf
+        light_pdf = distance_squared / (light_cos * area);
+    }
+
+    light_pdf = light_pdf / f32(params.emitter_count);
+
+    let cos_theta: f32 = dot(normal, direction);
+
+    if cos_theta <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+
+    // the emitter is visible if the shadow ray hits the same primitive
+    let shadow_hit: HitPayload = trace_ray(Ray(origin, direction));
+
+    if shadow_hit.hit_distance == F32_MAX || shadow_hit.primitive_type != emitter.primitive_type || shadow_hit.primitive_index != emitter.primitive_index {
+        return vec4<f32>(0.0);
+    }
+
+    let texture_size = vec2<i32>(i32(params.texture_width), i32(params.texture_height));
+
+    let light_material: SceneMaterial = sample_material_maps(material_array[shadow_hit.material_index], shadow_hit.texture_point, texture_size);
+    let light_color: vec4<f32> = srgb_to_linear(sample_texture(light_material.texture_index, shadow_hit.texture_point, texture_size));
+    let emitted_light: vec4<f32> = material_emission(light_material, light_color, shadow_hit.texture_point, texture_size);
+
+    let bsdf_pdf: f32 = diffuse_probability * cos_theta / PI;
+
+    return emitted_light * (cos_theta / PI) / light_pdf * power_heuristic(light_pdf, bsdf_pdf);
+}
+
+fn sample_lights(origin: vec3<f32>, normal: vec3<f32>, diffuse_probability: f32, rng: ptr<function, RandomState>) -> vec4<f32>{
+    // picks a random analytic light and casts a shadow ray towards it, the lights are not part of the geometry,
+    // only area lights can also be hit by rays, they are weighted against the lambertian lobe like the emitters
+
+    let light_index: u32 = min(u32(random(rng) * f32(params.light_count)), params.light_count - 1u);
+    let scene_light: SceneLight = light_array[light_index];
+
+    // switched off lights are skipped before any shadow ray is cast
+    if scene_light.intensity <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+
+    // only point lights shine without a direction, a zero vector would turn the whole sample into NaN
+    let direction_length: f32 = length(scene_light.direction);
+
+    if direction_length == 0.0 && scene_light.light_type != LIGHT_POINT {
+        return vec4<f32>(0.0);
+    }
+
+    let light_direction: vec3<f32> = scene_light.direction / max(direction_length, 1e-8);
+
+    var direction: vec3<f32>;
+    var distance: f32;
+    var falloff: f32;
+    var weight: f32 = 1.0;
+
+    switch scene_light.light_type {
+        case LIGHT_POINT, LIGHT_SPOT: {
+            let to_light: vec3<f32> = scene_light.position - origin;
+            distance = length(to_light);
+            direction = to_light / distance;
+
+            falloff = 1.0 / (distance * distance);
+
+            if scene_light.light_type == LIGHT_SPOT {
+                // light fades out between the inner and outer angle of the cone
+                let cos_angle: f32 = dot(-direction, light_direction);
+                let cos_outer: f32 = cos(radians(scene_light.spot_angle));
+                let cos_inner: f32 = cos(radians(scene_light.spot_angle * (1.0 - scene_light.spot_softness)));
+
+                let cone_falloff: f32 = clamp((cos_angle - cos_outer) / max(cos_inner - cos_outer, 0.0001), 0.0, 1.0);
+                falloff *= cone_falloff * cone_falloff;
+            }
+        }
+        case LIGHT_DIRECTIONAL: {
+            // sun light that comes from infinitely far away
+            direction = -light_direction;
+            distance = F32_MAX;
+            falloff = 1.0;
+        }
+        default: {
+            // one sided area light facing the light direction
+            let tangent: vec3<f32> = perpendicular(light_direction);
+            let bitangent: vec3<f32> = cross(light_direction, tangent);
+
+            let u: f32 = random(rng);
+            let v: f32 = random(rng);
+
+            var light_point: vec3<f32>;
+            var area: f32;
+
+            if scene_light.light_type == LIGHT_DISK {
+                let radius: f32 = scene_light.size.x * sqrt(u);
+                let phi: f32 = 2.0 * PI * v;
+
+                light_point = scene_light.position + (tangent * cos(phi) + bitangent * sin(phi)) * radius;
+                area = PI * scene_light.size.x * scene_light.size.x;
+            } else {
+                light_point = scene_light.position + tangent * (u - 0.5) * scene_light.size.x + bitangent * (v - 0.5) * scene_light.size.y;
+                area = scene_light.size.x * scene_light.size.y;
+            }
+
+            let to_light: vec3<f32> = light_point - origin;
+            distance = length(to_light);
+            direction = to_light / distance;
+
+            let light_cos: f32 = dot(-direction, light_direction);
+
+            // area pdf converted to solid angle
+            falloff = max(light_cos, 0.0) * area / (distance * distance);
+        }
+    }
+
+    let cos_theta: f32 = dot(normal, direction);
+
+    if cos_theta <= 0.0 || falloff <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+
+    if scene_light.light_type == LIGHT_RECTANGLE || scene_light.light_type == LIGHT_DISK {
+        let light_pdf: f32 = 1.0 / (falloff * f32(params.light_count));
+        let bsdf_pdf: f32 = diffuse_probability * cos_theta / PI;
+
+        weight = power_heuristic(light_pdf, bsdf_pdf);
+    }
+
+    // anything between the surface and the light blocks it
+    let shadow_hit: HitPayload = trace_ray(Ray(origin, direction));
+
+    if shadow_hit.hit_distance < distance - 0.001 {
+        return vec4<f32>(0.0);
+    }
+
+    let light_color: vec4<f32> = vec4<f32>(scene_light.color, 1.0) * scene_light.intensity;
+
+    // divided by the probability of picking this light
+    return light_color * falloff * (cos_theta / PI) * f32(params.light_count) * weight;
+}
+
+fn hit_area_lights(ray: Ray, max_distance: f32, previous_diffuse_probability: f32, previous_normal: vec3<f32>) -> vec4<f32>{
+    // light of the rectangle and disk lights whose front side the ray hits before the max distance,
+    // weighted against sample_lights when the ray comes from the lambertian lobe
+
+    let ray_direction: vec3<f32> = normalize(ray.direction);
+    var light = vec4<f32>(0.0);
+
+    for (var light_index: u32 = 0u; light_index < params.light_count; light_index = light_index + 1u) {
+        let scene_light: SceneLight = light_array[light_index];
+
+        let direction_length: f32 = length(scene_light.direction);
+
+        if scene_light.intensity <= 0.0 || direction_length == 0.0 || (scene_light.light_type != LIGHT_RECTANGLE && scene_light.light_type != LIGHT_DISK) {
+            continue;
+        }
+
+        let light_direction: vec3<f32> = scene_light.direction / direction_length;
+
+        // the light only shines from its front side
+        let light_cos: f32 = -dot(ray_direction, light_direction);
+
+        if light_cos <= 0.000001 {
+            continue;
+        }
+
+        // in lengths of the ray direction like the hit distance, the distance to the light is in world units
+        let ray_distance: f32 = dot(scene_light.position - ray.origin, light_direction) / dot(ray.direction, light_direction);
+
+        if ray_distance <= 0.0 || ray_distance >= max_distance {
+            continue;
+        }
+
+        let distance: f32 = ray_distance * length(ray.direction);
+        let offset: vec3<f32> = ray.origin + ray.direction * ray_distance - scene_light.position;
+        var area: f32;
+
+        if scene_light.light_type == LIGHT_DISK {
+            if length(offset) > scene_light.size.x {
+                continue;
+            }
+            area = PI * scene_light.size.x * scene_light.size.x;
+        } else {
+            // same axes as the points sampled on the rectangle
+            let tangent: vec3<f32> = perpendicular(light_direction);
+            let bitangent: vec3<f32> = cross(light_direction, tangent);
+
+            if abs(dot(offset, tangent)) > scene_light.size.x * 0.5 || abs(dot(offset, bitangent)) > scene_light.size.y * 0.5 {
+                continue;
+            }
+            area = scene_light.size.x * scene_light.size.y;
+        }
+
+        var weight: f32 = 1.0;
+
+        if previous_diffuse_probability > 0.0 {
+            let light_pdf: f32 = distance * distance / (light_cos * area * f32(params.light_count));
+            let bsdf_pdf: f32 = previous_diffuse_probability * max(dot(previous_normal, ray_direction), 0.0) / PI;
+
+            weight = power_heuristic(bsdf_pdf, light_pdf);
+        }
+
+        light += vec4<f32>(scene_light.color, 1.0) * scene_light.intensity * weight;
     }
+
     return light;
 }
 
+fn emitter_pdf(hit_payload: HitPayload, origin: vec3<f32>) -> f32{
+    // solid angle pdf of sample_emitters choosing the direction that hit this emitter
+
+    if params.emitter_count == 0 {
+        return 0.0;
+    }
+
+    var light_pdf: f32 = 0.0;
+
+    if hit_payload.primitive_type == PRIMITIVE_SPHERE {
+        let sphere: SceneSphere = sphere_array[hit_payload.primitive_index];
+
+        let to_center: vec3<f32> = sphere.position - origin;
+        let distance_squared: f32 = dot(to_center, to_center);
+        let radius_squared: f32 = sphere.radius * sphere.radius;
+
+        if distance_squared <= radius_squared {
+            return 0.0;
+        }
+
+        let cos_max: f32 = sqrt(1.0 - radius_squared / distance_squared);
+        light_pdf = 1.0 / (2.0 * PI * (1.0 - cos_max));
+
+    } else {
+        let tri: SceneTriangle = triangle_array[hit_payload.primitive_index];
+
+        let to_light: vec3<f32> = hit_payload.world_position - origin;
+        let distance_squared: f32 = dot(to_light, to_light);
+
+        // the normal of the hit is already moved to the time of the path
+        let light_cos: f32 = abs(dot(hit_payload.hitside_normal, normalize(to_light)));
+        let area: f32 = length(tri.calc_normal) * 0.5;
+
+        if light_cos < 0.000001 || area == 0.0 {
+            return 0.0;
+        }
+
+        light_pdf = distance_squared / (light_cos * area);
+    }
+
+    return light_pdf / f32(params.emitter_count);
+}
+
+fn power_heuristic(pdf: f32, other_pdf: f32) -> f32{
+    // multiple importance sampling weight
+    let pdf_squared: f32 = pdf * pdf;
+    let other_squared: f32 = other_pdf * other_pdf;
+
+    if pdf_squared + other_squared == 0.0 {
+        return 0.0;
+    }
+
+    return pdf_squared / (pdf_squared + other_squared);
+}
+
+fn primary_ray(normalized_coord: vec2<f32>, lens_sample: vec2<f32>) -> Ray {
+    // screen position between -1 and 1 to a ray in view space, the camera looks along -z
+    let view_ray: Ray = projection_ray(normalized_coord);
+
+    var view_origin: vec3<f32> = view_ray.origin;
+    var view_direction: vec3<f32> = view_ray.direction;
+
+    // the panoramic projections have no plane of focus
+    let has_lens: bool = ray_camera.projection_type == PROJECTION_PERSPECTIVE || ray_camera.projection_type == PROJECTION_ORTHOGRAPHIC;
+
+    if ray_camera.aperture_radius > 0.0 && has_lens {
+        // thin lens, every ray through the lens meets the pinhole ray on the plane of focus
+        let focus_point: vec3<f32> = view_origin + view_direction * (ray_camera.focus_distance / -view_direction.z);
+
+        view_origin += vec3<f32>(sample_aperture(lens_sample) * ray_camera.aperture_radius, 0.0);
+        view_direction = normalize(focus_point - view_origin);
+    }
+
+    let start_origin: vec3<f32> = (ray_camera.inverse_view * vec4<f32>(view_origin, 1.0)).xyz;
+    let start_direction: vec3<f32> = (ray_camera.inverse_view * vec4<f32>(view_direction, 0.0)).xyz;
+
+    // camera motion moves the ray between the start and end pose
+    let end_origin: vec3<f32> = (ray_camera.end_inverse_view * vec4<f32>(view_origin, 1.0)).xyz;
+    let end_direction: vec3<f32> = (ray_camera.end_inverse_view * vec4<f32>(view_direction, 0.0)).xyz;
+
+    let origin: vec3<f32> = mix(start_origin, end_origin, path_time);
+    let direction: vec3<f32> = mix(start_direction, end_direction, path_time);
+
+    return Ray(origin, normalize(direction));
+}
+
+fn projection_ray(normalized_coord: vec2<f32>) -> Ray {
+    let aspect_ratio: f32 = f32(params.width) / f32(params.height);
+
+    switch ray_camera.projection_type {
+        case PROJECTION_ORTHOGRAPHIC: {
+            // parallel rays from a plane the size of the view
+            let half_size = vec2<f32>(aspect_ratio, 1.0) * ray_camera.orthographic_height * 0.5;
+            return Ray(vec3<f32>(normalized_coord * half_size, 0.0), vec3<f32>(0.0, 0.0, -1.0));
+        }
+        case PROJECTION_EQUIRECTANGULAR: {
+            // longitude over the width and latitude over the height, the center looks forward
+            let phi: f32 = normalized_coord.x * PI;
+            let theta: f32 = normalized_coord.y * PI * 0.5;
+            let direction = vec3<f32>(sin(phi) * cos(theta), sin(theta), -cos(phi) * cos(theta));
+            return Ray(vec3<f32>(0.0), direction);
+        }
+        case PROJECTION_FISHEYE: {
+            // equidistant fisheye, the angle to the view direction grows linearly with the distance to the center
+            let position = vec2<f32>(normalized_coord.x * aspect_ratio, normalized_coord.y);
+            let radius: f32 = length(position);
+            let theta: f32 = radius * radians(ray_camera.fisheye_fov) * 0.5;
+
+            var side = vec2<f32>(0.0);
+            if radius > 0.0 {
+                side = position / radius * sin(theta);
+            }
+            return Ray(vec3<f32>(0.0), vec3<f32>(side, -cos(theta)));
+        }
+        default: {
+            let view_target: vec4<f32> = ray_camera.inverse_projection * vec4<f32>(normalized_coord, 1.0, 1.0);
+            return Ray(vec3<f32>(0.0), normalize(view_target.xyz / view_target.w));
+        }
+    }
+}
+
+fn inside_projection(normalized_coord: vec2<f32>) -> bool {
+    if ray_camera.projection_type != PROJECTION_FISHEYE {
+        return true;
+    }
+
+    let aspect_ratio: f32 = f32(params.width) / f32(params.height);
+    return length(vec2<f32>(normalized_coord.x * aspect_ratio, normalized_coord.y)) <= 1.0;
+}
+
+fn sample_aperture(u: vec2<f32>) -> vec2<f32> {
+    // uniform point on the unit aperture, a disk or a regular polygon with one corner per blade
+    let blades: u32 = ray_camera.aperture_blades;
+
+    if blades < 3u {
+        let r: f32 = sqrt(u.x);
+        let theta: f32 = 2.0 * PI * u.y;
+        return r * vec2<f32>(cos(theta), sin(theta));
+    }
+
+    // pick one of the triangles between the center and two neighbouring corners
+    let scaled: f32 = u.x * f32(blades);
+    let segment: f32 = min(floor(scaled), f32(blades - 1u));
+    let v: f32 = scaled - segment;
+
+    let angle: f32 = 2.0 * PI / f32(blades);
+    let corner_a = vec2<f32>(cos(segment * angle), sin(segment * angle));
+    let corner_b = vec2<f32>(cos((segment + 1.0) * angle), sin((segment + 1.0) * angle));
+
+    let r: f32 = sqrt(v);
+    return r * ((1.0 - u.y) * corner_a + u.y * corner_b);
+}
+
+fn sample_filter(rng: ptr<function, RandomState>) -> vec2<f32> {
+    // offset from the pixel center distributed like the filter weights,
+    // so every sample contributes with the same weight
+    let u = vec2<f32>(random(rng), random(rng));
+    let radius: f32 = params.filter_radius;
+
+    switch params.filter_type {
+        case FILTER_BOX: {
+            return (u * 2.0 - 1.0) * radius;
+        }
+        case FILTER_TENT: {
+            return vec2<f32>(sample_tent(u.x), sample_tent(u.y)) * radius;
+        }
+        case FILTER_GAUSSIAN: {
+            // standard deviation of a third of the radius, the gaussian is cut off at the radius
+            // by only inverting the part of its cumulative distribution that lies within it
+            let sigma: f32 = radius / 3.0;
+            let inside: f32 = 1.0 - exp(-radius * radius / (2.0 * sigma * sigma));
+            let r: f32 = sigma * sqrt(-2.0 * log(max(1.0 - u.x * inside, 0.0000001)));
+            let theta: f32 = 2.0 * PI * u.y;
+            return r * vec2<f32>(cos(theta), sin(theta));
+        }
+        default: {
+            return vec2<f32>(sample_blackman_harris(u.x), sample_blackman_harris(u.y)) * radius;
+        }
+    }
+}
+
+fn sample_tent(u: f32) -> f32 {
+    // inverse of the cumulative tent distribution from -1 to 1
+    if u < 0.5 {
+        return sqrt(2.0 * u) - 1.0;
+    }
+    return 1.0 - sqrt(2.0 - 2.0 * u);
+}
+
+fn sample_blackman_harris(u: f32) -> f32 {
+    // the cumulative blackman harris window has no closed inverse, so it gets inverted by bisection
+    var low: f32 = 0.0;
+    var high: f32 = 1.0;
+
+    for (var i: u32 = 0u; i < 16u; i = i + 1u) {
+        let t: f32 = (low + high) * 0.5;
+
+        let cdf: f32 = (0.35875 * t
+            - 0.48829 * sin(2.0 * PI * t) / (2.0 * PI)
+            + 0.14128 * sin(4.0 * PI * t) / (4.0 * PI)
+            - 0.01168 * sin(6.0 * PI * t) / (6.0 * PI)) / 0.35875;
+
+        if cdf < u {
+            low = t;
+        } else {
+            high = t;
+        }
+    }
+
+    // window position from 0 to 1 mapped to the filter support from -1 to 1
+    return (low + high) - 1.0;
+}
+
+fn sample_cone(axis: vec3<f32>, cos_max: f32, rng: ptr<function, RandomState>) -> vec3<f32>{
+    // uniformly distributed direction inside a cone around the axis
+    let cos_theta: f32 = 1.0 - random(rng) * (1.0 - cos_max);
+    let sin_theta: f32 = sqrt(max(0.0, 1.0 - cos_theta * cos_theta));
+    let phi: f32 = 2.0 * PI * random(rng);
+
+    let tangent: vec3<f32> = perpendicular(axis);
+    let bitangent: vec3<f32> = cross(axis, tangent);
+
+    return normalize(tangent * cos(phi) * sin_theta + bitangent * sin(phi) * sin_theta + axis * cos_theta);
+}
+
+fn perpendicular(axis: vec3<f32>) -> vec3<f32>{
+    // any unit vector perpendicular to the axis
+    var helper = vec3<f32>(1.0, 0.0, 0.0);
+
+    if abs(axis.x) > 0.9 {
+        helper = vec3<f32>(0.0, 1.0, 0.0);
+    }
+
+    return normalize(cross(helper, axis));
+}
+
+fn sample_material_maps(material: SceneMaterial, coords: vec2<f32>, texture_size: vec2<i32>) -> SceneMaterial{
+    // texture maps scale the scalar material values per texel,
+    // roughness is read from the green channel and specular from the blue channel like in glTF ORM textures,
+    // the blue channel holds the metalness there, here it scales the chance of a mirror like specular bounce
+    var surface_material: SceneMaterial = material;
+
+    if material.roughness_map_index != NO_TEXTURE {
+        surface_material.roughness *= sample_texture(material.roughness_map_index, coords, texture_size).g;
+    }
+
+    if material.specular_map_index != NO_TEXTURE {
+        surface_material.specular *= sample_texture(material.specular_map_index, coords, texture_size).b;
+    }
+
+    return surface_material;
+}
+
+fn perturb_normal(material: SceneMaterial, hit_payload: HitPayload, texture_size: vec2<i32>) -> vec3<f32>{
+    // bends the surface normal with the tangent space normal map and the height map of the material
+
+    if material.normal_map_index == NO_TEXTURE && material.height_map_index == NO_TEXTURE {
+        return hit_payload.hitside_normal;
+    }
+
+    // the maps are defined on the outside of the surface, so we work with the outward normal and flip it back at the end
+    var outward_normal: vec3<f32> = hit_payload.hitside_normal;
+
+    if !hit_payload.front_face {
+        outward_normal = -outward_normal;
+    }
+
+    let tangent: vec3<f32> = normalize(hit_payload.tangent.xyz - outward_normal * dot(outward_normal, hit_payload.tangent.xyz));
+    let bitangent: vec3<f32> = cross(outward_normal, tangent) * hit_payload.tangent.w;
+
+    var normal: vec3<f32> = outward_normal;
+
+    // both maps repeat outside of the 0 to 1 texture coordinates
+    let coords: vec2<f32> = fract(hit_payload.texture_point);
+
+    if material.normal_map_index != NO_TEXTURE {
+        let encoded_normal: vec3<f32> = sample_texture(material.normal_map_index, coords, texture_size).xyz;
+
+        var tangent_normal: vec3<f32> = encoded_normal * 2.0 - 1.0;
+        tangent_normal = vec3<f32>(tangent_normal.xy * material.normal_strength, tangent_normal.z);
+
+        normal = normalize(tangent * tangent_normal.x + bitangent * tangent_normal.y + normal * tangent_normal.z);
+    }
+
+    if material.height_map_index != NO_TEXTURE {
+        // height difference to the neighbouring texels gives the slope of the surface
+        let texel_size: vec2<f32> = 1.0 / vec2<f32>(texture_size);
+
+        let height: f32 = sample_texture(material.height_map_index, coords, texture_size).r;
+        let height_u: f32 = sample_texture(material.height_map_index, fract(coords + vec2<f32>(texel_size.x, 0.0)), texture_size).r;
+        let height_v: f32 = sample_texture(material.height_map_index, fract(coords + vec2<f32>(0.0, texel_size.y)), texture_size).r;
+
+        let slope = vec2<f32>(height_u - height, height_v - height) * material.normal_strength;
+
+        normal = normalize(normal - tangent * slope.x - bitangent * slope.y);
+    }
+
+    // a normal bent below the surface would send the rays inside the object
+    if dot(normal, outward_normal) <= 0.0 {
+        normal = outward_normal;
+    }
+
+    if !hit_payload.front_face {
+        normal = -normal;
+    }
+
+    return normal;
+}
+
+fn srgb_to_linear(color: vec4<f32>) -> vec4<f32>{
+    // color textures are stored as sRGB, light calculations have to be done in linear space
+    let low: vec3<f32> = color.rgb / 12.92;
+    let high: vec3<f32> = pow((color.rgb + 0.055) / 1.055, vec3<f32>(2.4));
+
+    return vec4<f32>(select(high, low, color.rgb <= vec3<f32>(0.04045)), color.a);
+}
+
 fn refract(ray_direction:vec3<f32>, hitside_normal: vec3<f32>, cos_theta: f32, refraction_index: f32) -> vec3<f32>{
     // the ray goes through the glass and the direction is changed based on refraction index
 
@@ -428,17 +1287,37 @@ fn check_triangles(ray: Ray) -> HitPayload{
         let object_info: ObjectInfo = object_array[object_index];
 
         // quick way to filter out objects that can't be hit with ray
-        if !ray_in_bounds(ray, object_info.min_bounds, object_info.max_bounds){
+        if !ray_in_bounds(ray, object_info.swept_min_bounds, object_info.swept_max_bounds){
             continue;
         }
 
+        // the triangles are stored at the start of the shutter,
+        // moving objects get the ray moved into that pose instead
+        var object_ray: Ray = ray;
+        var motion = mat3x3<f32>(
+            vec3<f32>(1.0, 0.0, 0.0),
+            vec3<f32>(0.0, 1.0, 0.0),
+            vec3<f32>(0.0, 0.0, 1.0),
+        );
+
+        if object_info.moving == 1u {
+            let current_translation: vec3<f32> = object_info.translation + object_info.motion_translation * path_time;
+
+            motion = object_motion(object_info);
+
+            object_ray = Ray(
+                transpose(motion) * (ray.origin - current_translation) + object_info.translation,
+                transpose(motion) * ray.direction,
+            );
+        }
+
         for (var i: u32 = 0; i < object_info.sub_object_count; i = i + 1){
             let sub_object_index = object_info.first_sub_object_index + i;
 
             let sub_object_info: SubObjectInfo = sub_object_array[sub_object_index];
 
                         
-            if !ray_in_bounds(ray, sub_object_info.min_bounds, sub_object_info.max_bounds){
+            if !ray_in_bounds(object_ray, sub_object_info.min_bounds, sub_object_info.max_bounds){
                 continue;
             }
 
@@ -446,11 +1325,11 @@ fn check_triangles(ray: Ray) -> HitPayload{
                 let triangle_index = sub_object_info.first_triangle_index + j;
                 let tri: SceneTriangle = triangle_array[triangle_index];
                 
-                let determinant: f32 = -dot(ray.direction, tri.calc_normal);
+                let determinant: f32 = -dot(object_ray.direction, tri.calc_normal);
 
                 let inv_det: f32 = 1 / determinant;
                 
-                let ao: vec3<f32> = ray.origin - tri.a; 
+                let ao: vec3<f32> = object_ray.origin - tri.a; 
 
                 let distance: f32 = dot(ao, tri.calc_normal) * inv_det;
 
@@ -458,7 +1337,7 @@ fn check_triangles(ray: Ray) -> HitPayload{
                     continue;
                 }
 
-                let dao: vec3<f32> = cross(ao, ray.direction); 
+                let dao: vec3<f32> = cross(ao, object_ray.direction); 
 
                 // calculate distance and intersection
 
@@ -494,18 +1373,23 @@ fn check_triangles(ray: Ray) -> HitPayload{
 
                 closest_distance = distance;
 
+                // rotations keep the length of the direction, so the distance is the same for the original ray
                 let hitpoint = ray.origin + ray.direction * distance;
 
-                let texture_coords = object_texture_coords(hitpoint, object_info.min_bounds, object_info.max_bounds);
+                let object_hitpoint = object_ray.origin + object_ray.direction * distance;
+                let texture_coords = object_texture_coords(object_hitpoint, object_info.min_bounds, object_info.max_bounds);
 
 
                 closest_hitpayload = HitPayload(
                     distance,
                     hitpoint,
-                    hitside_normal,
+                    motion * hitside_normal,
                     object_info.material_index,
                     front_face,
                     texture_coords,
+                    vec4<f32>(motion * tri.tangent, tri.tangent_sign),
+                    PRIMITIVE_TRIANGLE,
+                    triangle_index,
                 );
     
             };
@@ -516,13 +1400,49 @@ fn check_triangles(ray: Ray) -> HitPayload{
 
 }
 
+fn object_motion(object_info: ObjectInfo) -> mat3x3<f32>{
+    // rotation of a moving object from the start of the shutter to the time of the path
+    let start_rotation: mat3x3<f32> = rotation_matrix(object_info.rotation);
+    let current_rotation: mat3x3<f32> = rotation_matrix(object_info.rotation + object_info.motion_rotation * path_time);
+
+    return current_rotation * transpose(start_rotation);
+}
+
+fn rotation_matrix(degrees: vec3<f32>) -> mat3x3<f32>{
+    // euler rotation in the same order as the objects are rotated on the cpu, z * y * x
+    let angles: vec3<f32> = radians(degrees);
+    let c: vec3<f32> = cos(angles);
+    let s: vec3<f32> = sin(angles);
+
+    let rotation_x = mat3x3<f32>(
+        vec3<f32>(1.0, 0.0, 0.0),
+        vec3<f32>(0.0, c.x, s.x),
+        vec3<f32>(0.0, -s.x, c.x),
+    );
+    let rotation_y = mat3x3<f32>(
+        vec3<f32>(c.y, 0.0, -s.y),
+        vec3<f32>(0.0, 1.0, 0.0),
+        vec3<f32>(s.y, 0.0, c.y),
+    );
+    let rotation_z = mat3x3<f32>(
+        vec3<f32>(c.z, s.z, 0.0),
+        vec3<f32>(-s.z, c.z, 0.0),
+        vec3<f32>(0.0, 0.0, 1.0),
+    );
+
+    return rotation_z * rotation_y * rotation_x;
+}
+
 fn miss() -> HitPayload{ 
     return HitPayload(F32_MAX, 
     vec3<f32>(0.0),
     vec3<f32>(0.0),
     0u,
     false,
-    vec2<f32>(0.0)
+    vec2<f32>(0.0),
+    vec4<f32>(0.0),
+    U32_MAX,
+    U32_MAX,
     );
 }
 
@@ -535,6 +1455,13 @@ fn sphere_hit(ray: Ray, hit_distance: f32, object_index: u32) -> HitPayload{
 
     let texture_coords: vec2<f32> = sphere_texture_coords(outward_normal);
 
+    // direction of increasing u around the sphere, falls back to the X axis at the poles
+    var tangent: vec3<f32> = vec3<f32>(outward_normal.z, 0.0, -outward_normal.x);
+
+    if length(tangent) < 0.0001 {
+        tangent = vec3<f32>(1.0, 0.0, 0.0);
+    }
+
     let front_face = dot(ray.direction, outward_normal) < 0;
 
     var hitside_normal: vec3<f32>;
@@ -551,6 +1478,9 @@ fn sphere_hit(ray: Ray, hit_distance: f32, object_index: u32) -> HitPayload{
     closest_sphere.material_index,
     front_face,
     texture_coords,
+    vec4<f32>(normalize(tangent), 1.0),
+    PRIMITIVE_SPHERE,
+    object_index,
     );
 }
 
@@ -584,51 +1514,97 @@ fn environment_map_coords(ray_direction: vec3<f32>) -> vec2<f32>{
     return vec2<f32>(u, v);
 }
 
-fn random(seed: ptr<function, u32>) -> f32 {
+fn random(rng: ptr<function, RandomState>) -> f32 {
 
-    // random float between 0 and 1 using pcg hash
-    var state: u32 = *seed * 747796405u + 2891336453u;
+    // random float between 0 and 1 from a 2D padded owen scrambled sobol sequence,
+    // every pair of dimensions is a sobol point shuffled and scrambled with its own seed
+    let dimension: u32 = (*rng).dimension;
+    (*rng).dimension = dimension + 1u;
 
-    var word: u32 = (state >> ((state >> 28u) + 4u)) ^ state;
-    word = word * 277803737u;
+    let pair_seed: u32 = hash_combine((*rng).pixel_seed, dimension / 2u);
+    let shuffled_index: u32 = nested_uniform_scramble((*rng).sample_index, pair_seed);
 
-    // change seed value in place
-    *seed = (word >> 22u) ^ word;
+    var value: u32 = sobol_2d(shuffled_index, dimension % 2u);
+    value = nested_uniform_scramble(value, hash_combine(pair_seed, dimension % 2u + 1u));
 
-    return normalize_u32(*seed);
+    // only keep 24 bits so the float never rounds up to 1.0
+    return f32(value >> 8u) / 16777216.0;
 }
 
+fn sobol_2d(index: u32, dimension: u32) -> u32 {
+    // the first dimension is the van der corput sequence
+    if dimension == 0u {
+        return reverseBits(index);
+    }
 
-fn random_normal_scaler(seed: ptr<function, u32>) -> vec3<f32>{
-    // normally distributed random vec3 scaler from -1 to 1
-    var scaler = vec3<f32>(0.0);
-    scaler.x = normal_distribution(seed);
-    scaler.y = normal_distribution(seed);
-    scaler.z = normal_distribution(seed);
+    // the direction numbers of the second dimension follow from the polynomial x + 1
+    var value: u32 = 0u;
+    var direction: u32 = 0x80000000u;
+    var bits: u32 = index;
 
-    return scaler;
+    while bits != 0u {
+        if (bits & 1u) == 1u {
+            value = value ^ direction;
+        }
+        bits = bits >> 1u;
+        direction = direction ^ (direction >> 1u);
+    }
+
+    return value;
 }
 
-fn random_scaler(seed: ptr<function, u32>) -> vec3<f32>{
-    // random vec3 scaler from -1 to 1
-    var scaler = vec3<f32>(0.0);
-    scaler.x = random(seed);
-    scaler.y = random(seed);
-    scaler.z = random(seed);
+fn nested_uniform_scramble(value: u32, seed: u32) -> u32 {
+    // owen scrambling using the laine karras permutation on the reversed bits
+    var x: u32 = reverseBits(value);
+
+    x = x + seed;
+    x = x ^ (x * 0x6c50b47cu);
+    x = x ^ (x * 0xb82f1e52u);
+    x = x ^ (x * 0xc7afe638u);
+    x = x ^ (x * 0x8d22f6e6u);
 
-    return scaler * 2.0 - 1.0;
+    return reverseBits(x);
 }
 
-fn normal_distribution(seed: ptr<function, u32>) -> f32{
-    // returns normally distributed float
-    let theta: f32 = 2.0 * 3.1415926 * random(seed);
-    let rho: f32 = sqrt(-2.0 * log(random(seed)));
-    return rho * cos(theta);
+fn hash_combine(seed: u32, value: u32) -> u32 {
+    return seed ^ (hash_u32(value) + (seed << 6u) + (seed >> 2u));
+}
+
+fn hash_u32(value: u32) -> u32 {
+    // pcg hash
+    let state: u32 = value * 747796405u + 2891336453u;
+    let word: u32 = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
 
+
+fn cosine_direction(normal: vec3<f32>, rng: ptr<function, RandomState>) -> vec3<f32>{
+    // a random point on the unit sphere around the tip of the normal gives cosine weighted directions
+    let unit_vector: vec3<f32> = normalize(random_normal_scaler(rng));
+    let direction: vec3<f32> = normal + unit_vector;
+
+    // the point can land exactly opposite of the normal
+    if dot(direction, direction) < 1e-8 {
+        return normal;
+    }
+
+    return normalize(direction);
 }
 
-fn normalize_u32(value: u32) -> f32{
-    return f32(value) / f32(U32_MAX);
+fn random_normal_scaler(rng: ptr<function, RandomState>) -> vec3<f32>{
+    // normally distributed random vec3 scaler from -1 to 1
+    var scaler = vec3<f32>(0.0);
+    scaler.x = normal_distribution(rng);
+    scaler.y = normal_distribution(rng);
+    scaler.z = normal_distribution(rng);
+
+    return scaler;
 }
 
+fn normal_distribution(rng: ptr<function, RandomState>) -> f32{
+    // returns normally distributed float
+    let theta: f32 = 2.0 * 3.1415926 * random(rng);
+    let rho: f32 = sqrt(-2.0 * log(max(random(rng), 0.0000001)));
+    return rho * cos(theta);
 
+}
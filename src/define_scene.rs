@@ -1,21 +1,35 @@
 use super::triangle_object::ObjectCreation;
-use glam::vec3a;
+use glam::{Vec3A, vec3a};
 
 use super::triangle_object::load_stl_files;
 
-use super::buffers::{SceneMaterial, SceneSphere};
+use super::buffers::{
+    LIGHT_DIRECTIONAL, LIGHT_POINT, LIGHT_RECTANGLE, LIGHT_SPOT, NO_TEXTURE, SceneLight,
+    SceneMaterial, SceneSphere,
+};
 
 use super::renderer::RenderScene;
 
+use super::camera::{CAMERA_SETTINGS_FILE, CameraBookmark, CameraSettings};
+
+use super::camera_path::{CameraKeyframe, CameraPath};
+
 use super::image_texture::ImageTexture;
 
-pub(crate) fn define_render_scene() -> RenderScene {
+use image::Rgba;
+
+use std::path::Path;
+
+pub(crate) fn define_render_scene(scene_path: &Path) -> RenderScene {
+    // every file of the scene is loaded relative to the scene folder
+    let asset = |file: &str| scene_path.join(file).to_string_lossy().into_owned();
+
     // width and height for all images
 
     let env_map_size = [8192, 4096];
 
     let environment_map =
-        ImageTexture::new_from_image("./env_maps/studio_garden.png", env_map_size);
+        ImageTexture::new_from_image(&asset("env_maps/studio_garden.png"), env_map_size);
 
     //let environment_map = ImageTexture::new_from_color([0.2, 0.2, 0.2], env_map_size);
 
@@ -27,10 +41,10 @@ pub(crate) fn define_render_scene() -> RenderScene {
     let shiny_orange_texture = ImageTexture::new_from_color([1.0, 0.7, 0.0], texture_size); // 3
 
     // let cool_red_texture = ImageTexture::new_from_color([1.0, 0.0, 0.4], texture_size); // 4
-    let earth_texture = ImageTexture::new_from_image("./textures/earth.png", texture_size); // 4
+    let earth_texture = ImageTexture::new_from_image(&asset("textures/earth.png"), texture_size); // 4
     let shiny_white_texture = ImageTexture::new_from_color([1.0, 1.0, 1.0], texture_size); // 5
 
-    let _earth_texture = ImageTexture::new_from_image("./textures/earth.png", texture_size);
+    let _earth_texture = ImageTexture::new_from_image(&asset("textures/earth.png"), texture_size);
 
     // ###################### chess textures #####################################
 
@@ -48,7 +62,22 @@ pub(crate) fn define_render_scene() -> RenderScene {
     let w_bishop_texture = ImageTexture::new_from_color([1.0, 1.0, 1.0], texture_size); // 16
     let w_pawns_texture = ImageTexture::new_from_color([1.0, 1.0, 1.0], texture_size); // 17
 
-    let chess_board_texture = ImageTexture::new_from_image("./textures/chess.png", texture_size); // 18
+    let chess_board_texture =
+        ImageTexture::new_from_image(&asset("textures/chess.png"), texture_size); // 18
+
+    // the dark squares are pressed into the board
+    let chess_board_normal_texture =
+        ImageTexture::new_normal_map_from_image(&asset("textures/chess.png"), texture_size, 4.0); // 19
+
+    // only the land of the earth glows at night, the oceans and the ice stay dark
+    let earth_lights_texture = ImageTexture::new_from_image_pixels(
+        &asset("textures/earth.png"),
+        texture_size,
+        |Rgba([r, g, b, a])| match r.max(g) > b {
+            true => Rgba([255, 190, 110, a]),
+            false => Rgba([0, 0, 0, a]),
+        },
+    ); // 20
 
     // ###########################################################################
 
@@ -60,7 +89,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.0,
         glass: 1.0,
         refraction_index: 2.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
 
     let rough_blue = SceneMaterial {
@@ -71,7 +106,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 1.0,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
 
     let glossy_pink = SceneMaterial {
@@ -82,7 +123,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.1,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
 
     let shiny_orange = SceneMaterial {
@@ -93,18 +140,32 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.1,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
 
     let earth_material = SceneMaterial {
         texture_index: 4,
         roughness: 0.9,
         emission_power: 2.0,
-        specular: 0.0,
+        specular: 0.6,
         specular_scatter: 1.0,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        // the red land rises out of the blue oceans
+        height_map_index: 4,
+        normal_strength: 2.0,
+        // the dark green oceans are smooth and the blue in them reflects, like a packed ORM texture
+        roughness_map_index: 4,
+        specular_map_index: 4,
+        emission_map_index: 20,
+        _padding: [0; 12],
     };
 
     let shiny_white = SceneMaterial {
@@ -115,7 +176,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.1,
         glass: 1.0,
         refraction_index: 1.5,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
 
     // ###################### chess materials #####################################
@@ -128,7 +195,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let b_king_material = SceneMaterial {
         texture_index: 7,
@@ -138,7 +211,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let b_rook_material = SceneMaterial {
         texture_index: 8,
@@ -148,7 +227,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let b_knight_material = SceneMaterial {
         texture_index: 9,
@@ -158,7 +243,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let b_bishop_material = SceneMaterial {
         texture_index: 10,
@@ -168,7 +259,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let b_pawns_material = SceneMaterial {
         texture_index: 11,
@@ -178,7 +275,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let w_queen_material = SceneMaterial {
         texture_index: 12,
@@ -188,7 +291,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let w_king_material = SceneMaterial {
         texture_index: 13,
@@ -198,7 +307,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let w_rook_material = SceneMaterial {
         texture_index: 14,
@@ -208,7 +323,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let w_knight_material = SceneMaterial {
         texture_index: 15,
@@ -218,7 +339,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let w_bishop_material = SceneMaterial {
         texture_index: 16,
@@ -228,7 +355,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
     let w_pawns_material = SceneMaterial {
         texture_index: 17,
@@ -238,7 +371,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.2,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: NO_TEXTURE,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
 
     let chess_board_material = SceneMaterial {
@@ -249,7 +388,13 @@ pub(crate) fn define_render_scene() -> RenderScene {
         specular_scatter: 0.1,
         glass: 0.0,
         refraction_index: 1.0,
-        _padding: [0; 4],
+        normal_map_index: 19,
+        height_map_index: NO_TEXTURE,
+        normal_strength: 1.0,
+        roughness_map_index: NO_TEXTURE,
+        specular_map_index: NO_TEXTURE,
+        emission_map_index: NO_TEXTURE,
+        _padding: [0; 12],
     };
 
     // ###########################################################################
@@ -275,6 +420,61 @@ pub(crate) fn define_render_scene() -> RenderScene {
         _padding: [0; 12],
     };
 
+    // ###################### lights #####################################
+
+    // the chess scene is lit by the environment map and the glowing spheres,
+    // these lights start switched off and can be turned on by raising their intensity in the UI
+
+    let point_light: SceneLight = SceneLight {
+        position: [0.0, -8.0, 5.0],
+        light_type: LIGHT_POINT,
+        direction: [0.0, 1.0, 0.0],
+        intensity: 0.0,
+        color: [1.0, 0.85, 0.7],
+        spot_angle: 0.0,
+        size: [0.0, 0.0],
+        spot_softness: 0.0,
+        _padding: [0; 4],
+    };
+
+    let board_spot_light: SceneLight = SceneLight {
+        position: [0.0, -12.0, 0.0],
+        light_type: LIGHT_SPOT,
+        direction: [0.0, 1.0, 0.0],
+        intensity: 0.0,
+        color: [1.0, 1.0, 1.0],
+        spot_angle: 30.0,
+        size: [0.0, 0.0],
+        spot_softness: 0.3,
+        _padding: [0; 4],
+    };
+
+    let sun_light: SceneLight = SceneLight {
+        position: [0.0, 0.0, 0.0],
+        light_type: LIGHT_DIRECTIONAL,
+        direction: [0.4, 1.0, -0.3],
+        intensity: 0.0,
+        color: [1.0, 0.95, 0.85],
+        spot_angle: 0.0,
+        size: [0.0, 0.0],
+        spot_softness: 0.0,
+        _padding: [0; 4],
+    };
+
+    let window_light: SceneLight = SceneLight {
+        position: [-8.0, -10.0, 8.0],
+        light_type: LIGHT_RECTANGLE,
+        direction: [0.5, 1.0, -0.5],
+        intensity: 0.0,
+        color: [0.7, 0.8, 1.0],
+        spot_angle: 0.0,
+        size: [4.0, 2.0],
+        spot_softness: 0.0,
+        _padding: [0; 4],
+    };
+
+    // ###########################################################################
+
     let b_position = vec3a(5.3, -0.7, 0.0);
     let b_rotation = vec3a(90.0, 0.0, 0.0);
 
@@ -306,7 +506,7 @@ pub(crate) fn define_render_scene() -> RenderScene {
     let object_vec = load_stl_files(&[
         // ################# floor ####################
         ObjectCreation {
-            file_path: "./3D_models/Wall.stl".to_string(),
+            file_path: asset("3D_models/Wall.stl"),
             scale: 200.0,
             coordinates: vec3a(0.0, 7.066, 0.0),
             rotation: vec3a(0.0, 0.0, 0.0),
@@ -316,112 +516,112 @@ pub(crate) fn define_render_scene() -> RenderScene {
 
         // ############### black pieces #######################
         ObjectCreation {
-            file_path: "./3D_models/Queen.stl".to_string(),
+            file_path: asset("3D_models/Queen.stl"),
             scale: 2.0,
             coordinates: b_position + queen_offset,
             rotation: b_rotation,
             material_index: 6,
         },
         ObjectCreation {
-            file_path: "./3D_models/King.stl".to_string(),
+            file_path: asset("3D_models/King.stl"),
             scale: 2.0,
             coordinates: b_position + king_offset,
             rotation: b_rotation,
             material_index: 7,
         },
         ObjectCreation {
-            file_path: "./3D_models/Rook.stl".to_string(),
+            file_path: asset("3D_models/Rook.stl"),
             scale: 2.0,
             coordinates: b_position + rook_offset,
             rotation: b_rotation,
             material_index: 8,
         },
         ObjectCreation {
-            file_path: "./3D_models/Rook.stl".to_string(),
+            file_path: asset("3D_models/Rook.stl"),
             scale: 2.0,
             coordinates: b_position - rook_offset,
             rotation: b_rotation,
             material_index: 8,
         },
         ObjectCreation {
-            file_path: "./3D_models/Knight.stl".to_string(),
+            file_path: asset("3D_models/Knight.stl"),
             scale: 2.0,
             coordinates: b_position + knight_offset,
             rotation: b_rotation,
             material_index: 9,
         },
         ObjectCreation {
-            file_path: "./3D_models/Knight.stl".to_string(),
+            file_path: asset("3D_models/Knight.stl"),
             scale: 2.0,
             coordinates: b_position - knight_offset,
             rotation: b_rotation,
             material_index: 9,
         },
         ObjectCreation {
-            file_path: "./3D_models/Bishop.stl".to_string(),
+            file_path: asset("3D_models/Bishop.stl"),
             scale: 2.0,
             coordinates: b_position + bishop_offset,
             rotation: b_rotation,
             material_index: 10,
         },
         ObjectCreation {
-            file_path: "./3D_models/Bishop.stl".to_string(),
+            file_path: asset("3D_models/Bishop.stl"),
             scale: 2.0,
             coordinates: b_position - bishop_offset,
             rotation: b_rotation,
             material_index: 10,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: b_position + pawn1_offset,
             rotation: b_rotation,
             material_index: 11,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: b_position + pawn2_offset,
             rotation: b_rotation,
             material_index: 11,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: b_position + pawn3_offset,
             rotation: b_rotation,
             material_index: 11,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: b_position + pawn4_offset,
             rotation: b_rotation,
             material_index: 11,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: b_position + pawn5_offset,
             rotation: b_rotation,
             material_index: 11,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: b_position + pawn6_offset,
             rotation: b_rotation,
             material_index: 11,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: b_position + pawn7_offset,
             rotation: b_rotation,
             material_index: 11,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: b_position + pawn8_offset,
             rotation: b_rotation,
@@ -429,119 +629,119 @@ pub(crate) fn define_render_scene() -> RenderScene {
         },
         // ############### white pieces #######################
         ObjectCreation {
-            file_path: "./3D_models/Queen.stl".to_string(),
+            file_path: asset("3D_models/Queen.stl"),
             scale: 2.0,
             coordinates: w_position - queen_offset,
             rotation: w_rotation,
             material_index: 12,
         },
         ObjectCreation {
-            file_path: "./3D_models/King.stl".to_string(),
+            file_path: asset("3D_models/King.stl"),
             scale: 2.0,
             coordinates: w_position - king_offset,
             rotation: w_rotation,
             material_index: 13,
         },
         ObjectCreation {
-            file_path: "./3D_models/Rook.stl".to_string(),
+            file_path: asset("3D_models/Rook.stl"),
             scale: 2.0,
             coordinates: w_position + rook_offset,
             rotation: w_rotation,
             material_index: 14,
         },
         ObjectCreation {
-            file_path: "./3D_models/Rook.stl".to_string(),
+            file_path: asset("3D_models/Rook.stl"),
             scale: 2.0,
             coordinates: w_position - rook_offset,
             rotation: w_rotation,
             material_index: 14,
         },
         ObjectCreation {
-            file_path: "./3D_models/Knight.stl".to_string(),
+            file_path: asset("3D_models/Knight.stl"),
             scale: 2.0,
             coordinates: w_position + knight_offset,
             rotation: w_rotation,
             material_index: 15,
         },
         ObjectCreation {
-            file_path: "./3D_models/Knight.stl".to_string(),
+            file_path: asset("3D_models/Knight.stl"),
             scale: 2.0,
             coordinates: w_position - knight_offset,
             rotation: w_rotation,
             material_index: 15,
         },
         ObjectCreation {
-            file_path: "./3D_models/Bishop.stl".to_string(),
+            file_path: asset("3D_models/Bishop.stl"),
             scale: 2.0,
             coordinates: w_position + bishop_offset,
             rotation: w_rotation,
             material_index: 16,
         },
         ObjectCreation {
-            file_path: "./3D_models/Bishop.stl".to_string(),
+            file_path: asset("3D_models/Bishop.stl"),
             scale: 2.0,
             coordinates: w_position - bishop_offset,
             rotation: w_rotation,
             material_index: 16,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: w_position - pawn1_offset,
             rotation: w_rotation,
             material_index: 17,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: w_position - pawn2_offset,
             rotation: w_rotation,
             material_index: 17,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: w_position - pawn3_offset,
             rotation: w_rotation,
             material_index: 17,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: w_position - pawn4_offset,
             rotation: w_rotation,
             material_index: 17,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: w_position - pawn5_offset,
             rotation: w_rotation,
             material_index: 17,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: w_position - pawn6_offset,
             rotation: w_rotation,
             material_index: 17,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: w_position - pawn7_offset,
             rotation: w_rotation,
             material_index: 17,
         },
         ObjectCreation {
-            file_path: "./3D_models/Pawn.stl".to_string(),
+            file_path: asset("3D_models/Pawn.stl"),
             scale: 2.0,
             coordinates: w_position - pawn8_offset,
             rotation: w_rotation,
             material_index: 17,
         },
         ObjectCreation {
-            file_path: "./3D_models/Wall.stl".to_string(),
+            file_path: asset("3D_models/Wall.stl"),
             scale: 20.0,
             coordinates: vec3a(0.0, 0.0, 0.0),
             rotation: vec3a(0.0, 90.0, 0.0),
@@ -550,6 +750,26 @@ pub(crate) fn define_render_scene() -> RenderScene {
         // ###########################################################################
     ]);
 
+    // camera path sweeping around the front of the board, always looking at its center
+    let board_center = vec3a(0.0, -1.0, 0.0);
+
+    let path_keyframe = |time: f32, position: Vec3A, vertical_fov: f32| CameraKeyframe {
+        time,
+        position,
+        direction: (board_center - position).normalize(),
+        vertical_fov,
+    };
+
+    let camera_path = CameraPath {
+        keyframes: vec![
+            path_keyframe(0.0, vec3a(-20.0, -8.0, 18.0), 45.0),
+            path_keyframe(3.0, vec3a(0.0, -6.0, 25.0), 45.0),
+            path_keyframe(6.0, vec3a(20.0, -8.0, 18.0), 45.0),
+            path_keyframe(9.0, vec3a(10.0, -14.0, 8.0), 35.0),
+        ],
+        frames_per_second: 24.0,
+    };
+
     RenderScene {
         image_textures: vec![
             shiny_green_texture,
@@ -571,6 +791,8 @@ pub(crate) fn define_render_scene() -> RenderScene {
             w_bishop_texture,
             w_pawns_texture,
             chess_board_texture,
+            chess_board_normal_texture,
+            earth_lights_texture,
         ],
         texture_size,
 
@@ -597,6 +819,63 @@ pub(crate) fn define_render_scene() -> RenderScene {
         ],
         spheres: vec![sphere_a, sphere_b, shiny_sphere],
         objects: object_vec,
+        lights: vec![point_light, board_spot_light, sun_light, window_light],
+        // a camera.toml in the scene folder can change the camera without recompiling
+        camera: CameraSettings::load(
+            &scene_path.join(CAMERA_SETTINGS_FILE),
+            CameraSettings {
+                position: vec3a(0.0, -6.0, 25.0),
+                direction: vec3a(0.0, 0.0, -1.0),
+                vertical_fov: 45.0,
+                near_clip: 0.1,
+                far_clip: 100.0,
+                movement_speed: 0.5,
+                turning_speed: 0.001,
+            },
+        ),
+        camera_path,
+        camera_bookmarks: vec![
+            CameraBookmark {
+                name: "overview".to_string(),
+                position: vec3a(0.0, -6.0, 25.0),
+                direction: vec3a(0.0, 0.0, -1.0),
+                vertical_fov: 45.0,
+                aperture_radius: 0.0,
+                focus_distance: 25.0,
+            },
+            CameraBookmark {
+                name: "white side".to_string(),
+                position: vec3a(-22.0, -9.0, 0.0),
+                direction: vec3a(1.0, 0.4, 0.0).normalize(),
+                vertical_fov: 45.0,
+                aperture_radius: 0.0,
+                focus_distance: 22.0,
+            },
+            CameraBookmark {
+                name: "black side".to_string(),
+                position: vec3a(22.0, -9.0, 0.0),
+                direction: vec3a(-1.0, 0.4, 0.0).normalize(),
+                vertical_fov: 45.0,
+                aperture_radius: 0.0,
+                focus_distance: 22.0,
+            },
+            CameraBookmark {
+                name: "top down".to_string(),
+                position: vec3a(0.0, -30.0, 6.0),
+                direction: vec3a(0.0, 1.0, -0.2).normalize(),
+                vertical_fov: 45.0,
+                aperture_radius: 0.0,
+                focus_distance: 30.0,
+            },
+            CameraBookmark {
+                name: "pawn close up".to_string(),
+                position: vec3a(-2.0, -2.5, 8.0),
+                direction: vec3a(-0.3, 0.15, -1.0).normalize(),
+                vertical_fov: 35.0,
+                aperture_radius: 0.15,
+                focus_distance: 8.0,
+            },
+        ],
         environment_map,
         env_map_size,
     }
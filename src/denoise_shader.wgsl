@@ -0,0 +1,108 @@
+// edge-avoiding à-trous wavelet filter (Dammertz et al. 2010), every iteration spreads the same 5x5 kernel
+// twice as wide and stops at the edges of the albedo, normal and depth of the surfaces seen by the camera
+
+struct DenoiseParams {
+    step_width: u32,
+    last_iteration: u32,
+};
+
+@group(0) @binding(0) var<uniform> denoise_params: DenoiseParams;
+@group(0) @binding(1) var color_input: texture_2d<f32>;
+@group(0) @binding(2) var albedo_input: texture_2d<f32>;
+@group(0) @binding(3) var normal_depth_input: texture_2d<f32>;
+@group(0) @binding(4) var color_output: texture_storage_2d<rgba32float, write>;
+
+// how fast the weights fall off for different colors, albedos, normals and depths
+const COLOR_PHI: f32 = 4.0;
+const ALBEDO_PHI: f32 = 0.1;
+const NORMAL_POWER: f32 = 64.0;
+const DEPTH_PHI: f32 = 0.05;
+
+// albedos darker than this are not divided out, it would only amplify the noise
+const MIN_ALBEDO: f32 = 0.01;
+
+// 1D weights of the B3 spline, the 5x5 kernel is their product
+const KERNEL = array<f32, 5>(0.0625, 0.25, 0.375, 0.25, 0.0625);
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let size = vec2<i32>(textureDimensions(color_input));
+    let pixel = vec2<i32>(global_id.xy);
+
+    if pixel.x >= size.x || pixel.y >= size.y {
+        return;
+    }
+
+    let center_albedo: vec3<f32> = textureLoad(albedo_input, pixel, 0).rgb;
+    let center_normal_depth: vec4<f32> = textureLoad(normal_depth_input, pixel, 0);
+    let center_color: vec4<f32> = load_color(pixel, center_albedo);
+
+    // the color difference that is still averaged shrinks with every iteration, the noise is already lower
+    let color_phi: f32 = COLOR_PHI / f32(denoise_params.step_width);
+
+    // a local copy of the kernel can be indexed with the loop offsets
+    var kernel = KERNEL;
+
+    var color_sum = vec4<f32>(0.0);
+    var weight_sum: f32 = 0.0;
+
+    for (var y: i32 = -2; y <= 2; y++) {
+        for (var x: i32 = -2; x <= 2; x++) {
+            // the image is not repeated past its edges, the pixels there get counted twice instead
+            let sample_pixel: vec2<i32> = clamp(pixel + vec2<i32>(x, y) * i32(denoise_params.step_width), vec2<i32>(0), size - 1);
+
+            let albedo: vec3<f32> = textureLoad(albedo_input, sample_pixel, 0).rgb;
+            let normal_depth: vec4<f32> = textureLoad(normal_depth_input, sample_pixel, 0);
+            let color: vec4<f32> = load_color(sample_pixel, albedo);
+
+            // compressed colors keep single very bright samples from dominating the difference
+            let color_difference: vec3<f32> = compress(color.rgb) - compress(center_color.rgb);
+            let color_weight: f32 = exp(-dot(color_difference, color_difference) / color_phi);
+
+            let albedo_difference: vec3<f32> = albedo - center_albedo;
+            let albedo_weight: f32 = exp(-dot(albedo_difference, albedo_difference) / ALBEDO_PHI);
+
+            // the sky has no normal, it only matches other sky pixels
+            var normal_weight: f32 = 1.0;
+            if any(center_normal_depth.xyz != normal_depth.xyz) {
+                normal_weight = pow(max(dot(center_normal_depth.xyz, normal_depth.xyz), 0.0), NORMAL_POWER);
+            }
+
+            // depths are compared relative to the distance, far surfaces have larger gaps between the pixels
+            let depth_difference: f32 = abs(normal_depth.w - center_normal_depth.w) / max(center_normal_depth.w, 1e-4);
+            let depth_weight: f32 = exp(-depth_difference / (DEPTH_PHI * f32(denoise_params.step_width)));
+
+            let weight: f32 = kernel[x + 2] * kernel[y + 2] * color_weight * albedo_weight * normal_weight * depth_weight;
+
+            color_sum += color * weight;
+            weight_sum += weight;
+        }
+    }
+
+    // the center pixel always has a weight, so the sum is never zero
+    var filtered: vec4<f32> = color_sum / weight_sum;
+
+    if denoise_params.last_iteration == 1u {
+        // the texture detail divided out in the first iteration is multiplied back in,
+        // the display pass tone maps the result
+        filtered = vec4<f32>(filtered.rgb * max(center_albedo, vec3<f32>(MIN_ALBEDO)), filtered.a);
+    }
+
+    textureStore(color_output, pixel, filtered);
+}
+
+fn load_color(pixel: vec2<i32>, albedo: vec3<f32>) -> vec4<f32> {
+    // the first iteration reads the radiance of the path tracer and divides out the albedo,
+    // so only the lighting is blurred and textures stay sharp, later iterations read the filtered lighting
+    let color: vec4<f32> = textureLoad(color_input, pixel, 0);
+
+    if denoise_params.step_width == 1u {
+        return vec4<f32>(color.rgb / max(albedo, vec3<f32>(MIN_ALBEDO)), color.a);
+    }
+
+    return color;
+}
+
+fn compress(color: vec3<f32>) -> vec3<f32> {
+    return color / (1.0 + color);
+}
@@ -0,0 +1,206 @@
+use crate::buffers::{
+    self, DataBuffers, RADIANCE_FORMAT, storage_texture_layout_entry, texture_layout_entry,
+};
+
+use wgpu::{
+    BindGroup, BindGroupLayout, Buffer, CommandEncoder, ComputePipeline, Device, Queue, Texture,
+    util::DeviceExt,
+};
+
+// every iteration doubles the distance between the filtered pixels, 5 of them reach 62 pixels far
+const ITERATIONS: u32 = 5;
+
+// how far the pixels that are filtered into a denoised pixel can be away from it
+pub const DENOISE_REACH: u32 = 2 * ((1 << ITERATIONS) - 1);
+
+// bindings of the denoise shader
+const DENOISE_PARAMS_BIND: u32 = 0;
+const COLOR_INPUT_BIND: u32 = 1;
+const ALBEDO_INPUT_BIND: u32 = 2;
+const NORMAL_DEPTH_INPUT_BIND: u32 = 3;
+const COLOR_OUTPUT_BIND: u32 = 4;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct DenoiseParams {
+    step_width: u32,     // u32, aligned to 4 bytes
+    last_iteration: u32, // u32, aligned to 4 bytes
+    _padding: [u32; 2],  // padding to ensure 16-byte alignment
+}
+
+pub struct Denoiser {
+    pipeline: ComputePipeline,
+    bind_group_layout: BindGroupLayout,
+    params_buffers: Vec<Buffer>, // one per iteration, so all of them can be recorded in one pass
+    filtered_textures: [Texture; 2], // the iterations read from one and write to the other
+    bind_groups: Vec<BindGroup>,
+}
+
+impl Denoiser {
+    pub fn new(device: &Device, buffers: &DataBuffers) -> Denoiser {
+        let bind_group_layout = create_denoise_bindgroup_layout(device);
+        let pipeline = create_denoise_pipeline(device, &bind_group_layout);
+
+        let params_buffers = (0..ITERATIONS)
+            .map(|iteration| {
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Denoise Params Buffer"),
+                    contents: bytemuck::cast_slice(&[denoise_params(iteration)]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                })
+            })
+            .collect();
+
+        let filtered_textures = create_filtered_textures(device, buffers);
+
+        let mut denoiser = Denoiser {
+            pipeline,
+            bind_group_layout,
+            params_buffers,
+            filtered_textures,
+            bind_groups: Vec::new(),
+        };
+
+        denoiser.bind_groups = denoiser.create_bind_groups(device, buffers);
+        denoiser
+    }
+
+    pub fn resize(&mut self, device: &Device, buffers: &DataBuffers) {
+        // the pixel textures of the compute shader were made again, the bind groups point to the old ones
+        self.filtered_textures = create_filtered_textures(device, buffers);
+        self.bind_groups = self.create_bind_groups(device, buffers);
+    }
+
+    pub fn denoise(&self, encoder: &mut CommandEncoder) {
+        // filters the radiance of the last compute frame into the denoised texture
+        let size = self.filtered_textures[0].size();
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Denoise Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.pipeline);
+
+        for bind_group in &self.bind_groups {
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            compute_pass.dispatch_workgroups(size.width.div_ceil(8), size.height.div_ceil(8), 1);
+        }
+    }
+
+    pub fn denoised_texture(&self) -> &Texture {
+        // linear radiance of the last iteration, before the exposure and the tone mapping
+        &self.filtered_textures[((ITERATIONS - 1) % 2) as usize]
+    }
+
+    pub fn read_denoised(&self, device: &Device, queue: &Queue) -> Vec<[f32; 4]> {
+        buffers::read_texture(device, queue, self.denoised_texture())
+    }
+
+    fn create_bind_groups(&self, device: &Device, buffers: &DataBuffers) -> Vec<BindGroup> {
+        let view = |texture: &Texture| texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let albedo_view = view(&buffers.albedo_texture);
+        let normal_depth_view = view(&buffers.normal_depth_texture);
+        let radiance_view = view(&buffers.radiance_texture);
+        let filtered_views = self.filtered_textures.each_ref().map(view);
+
+        (0..ITERATIONS as usize)
+            .map(|iteration| {
+                // the first iteration reads the radiance of the path tracer
+                let input_view = match iteration {
+                    0 => &radiance_view,
+                    _ => &filtered_views[(iteration - 1) % 2],
+                };
+                let output_view = &filtered_views[iteration % 2];
+
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: DENOISE_PARAMS_BIND,
+                            resource: self.params_buffers[iteration].as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: COLOR_INPUT_BIND,
+                            resource: wgpu::BindingResource::TextureView(input_view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: ALBEDO_INPUT_BIND,
+                            resource: wgpu::BindingResource::TextureView(&albedo_view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: NORMAL_DEPTH_INPUT_BIND,
+                            resource: wgpu::BindingResource::TextureView(&normal_depth_view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: COLOR_OUTPUT_BIND,
+                            resource: wgpu::BindingResource::TextureView(output_view),
+                        },
+                    ],
+                    label: Some("Denoise Bind Group"),
+                })
+            })
+            .collect()
+    }
+}
+
+fn denoise_params(iteration: u32) -> DenoiseParams {
+    DenoiseParams {
+        step_width: 1 << iteration,
+        last_iteration: (iteration == ITERATIONS - 1) as u32,
+        _padding: [0; 2],
+    }
+}
+
+fn create_filtered_textures(device: &Device, buffers: &DataBuffers) -> [Texture; 2] {
+    // same size as the pixel textures of the compute shader
+    let size = buffers.radiance_texture.size();
+
+    [(); 2].map(|_| buffers::create_pixel_texture(device, size.width, size.height, RADIANCE_FORMAT))
+}
+
+fn create_denoise_pipeline(
+    device: &Device,
+    bind_group_layout: &BindGroupLayout,
+) -> ComputePipeline {
+    let denoise_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("denoise_shader.wgsl"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("denoise_shader.wgsl").into()),
+    });
+
+    let denoise_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Denoise Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Denoise Pipeline"),
+        layout: Some(&denoise_pipeline_layout),
+        module: &denoise_module,
+        entry_point: "main",
+        compilation_options: wgpu::PipelineCompilationOptions::default(),
+    })
+}
+
+fn create_denoise_bindgroup_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: DENOISE_PARAMS_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            texture_layout_entry(COLOR_INPUT_BIND),
+            texture_layout_entry(ALBEDO_INPUT_BIND),
+            texture_layout_entry(NORMAL_DEPTH_INPUT_BIND),
+            storage_texture_layout_entry(COLOR_OUTPUT_BIND, RADIANCE_FORMAT),
+        ],
+        label: Some("Denoise Bind Group Layout"),
+    })
+}
@@ -0,0 +1,199 @@
+use crate::buffers::{
+    DataBuffers, OUTPUT_FORMAT, storage_texture_layout_entry, texture_layout_entry,
+};
+
+use crate::denoiser::Denoiser;
+
+use wgpu::{
+    BindGroup, BindGroupLayout, Buffer, CommandEncoder, ComputePipeline, Device, Queue, Texture,
+    util::DeviceExt,
+};
+
+// bindings of the display shader
+const DISPLAY_PARAMS_BIND: u32 = 0;
+const RADIANCE_INPUT_BIND: u32 = 1;
+const DISPLAY_OUTPUT_BIND: u32 = 2;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct DisplayParams {
+    exposure: f32,      // f32, aligned to 4 bytes
+    tone_mapper: u32,   // u32, aligned to 4 bytes
+    _padding: [u32; 2], // padding to ensure 16-byte alignment
+}
+
+pub struct DisplayPass {
+    pipeline: ComputePipeline,
+    bind_group_layout: BindGroupLayout,
+    params_buffer: Buffer,
+    radiance_bind_group: BindGroup, // reads the radiance of the path tracer
+    denoised_bind_group: BindGroup, // reads the radiance of the denoiser
+    size: wgpu::Extent3d,
+}
+
+impl DisplayPass {
+    pub fn new(device: &Device, buffers: &DataBuffers, denoiser: &Denoiser) -> DisplayPass {
+        let bind_group_layout = create_display_bindgroup_layout(device);
+        let pipeline = create_display_pipeline(device, &bind_group_layout);
+
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Display Params Buffer"),
+            contents: bytemuck::cast_slice(&[display_params(0.0, 0)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let radiance_bind_group = create_display_bindgroup(
+            device,
+            &bind_group_layout,
+            &params_buffer,
+            &buffers.radiance_texture,
+            &buffers.output_texture,
+        );
+        let denoised_bind_group = create_display_bindgroup(
+            device,
+            &bind_group_layout,
+            &params_buffer,
+            denoiser.denoised_texture(),
+            &buffers.output_texture,
+        );
+
+        DisplayPass {
+            pipeline,
+            bind_group_layout,
+            params_buffer,
+            radiance_bind_group,
+            denoised_bind_group,
+            size: buffers.output_texture.size(),
+        }
+    }
+
+    pub fn resize(&mut self, device: &Device, buffers: &DataBuffers, denoiser: &Denoiser) {
+        // the pixel textures were made again, the bind groups point to the old ones
+        self.radiance_bind_group = create_display_bindgroup(
+            device,
+            &self.bind_group_layout,
+            &self.params_buffer,
+            &buffers.radiance_texture,
+            &buffers.output_texture,
+        );
+        self.denoised_bind_group = create_display_bindgroup(
+            device,
+            &self.bind_group_layout,
+            &self.params_buffer,
+            denoiser.denoised_texture(),
+            &buffers.output_texture,
+        );
+        self.size = buffers.output_texture.size();
+    }
+
+    pub fn display(
+        &self,
+        queue: &Queue,
+        encoder: &mut CommandEncoder,
+        exposure: f32,
+        tone_mapper: u32,
+        denoised: bool,
+    ) {
+        // writes the tone mapped image to the output texture, from the denoised radiance when it is turned on
+        let params = display_params(exposure, tone_mapper);
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params]));
+
+        let bind_group = match denoised {
+            true => &self.denoised_bind_group,
+            false => &self.radiance_bind_group,
+        };
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Display Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, bind_group, &[]);
+        compute_pass.dispatch_workgroups(
+            self.size.width.div_ceil(8),
+            self.size.height.div_ceil(8),
+            1,
+        );
+    }
+}
+
+fn display_params(exposure: f32, tone_mapper: u32) -> DisplayParams {
+    DisplayParams {
+        exposure,
+        tone_mapper,
+        _padding: [0; 2],
+    }
+}
+
+fn create_display_bindgroup(
+    device: &Device,
+    bind_group_layout: &BindGroupLayout,
+    params_buffer: &Buffer,
+    radiance_texture: &Texture,
+    output_texture: &Texture,
+) -> BindGroup {
+    let view = |texture: &Texture| texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: DISPLAY_PARAMS_BIND,
+                resource: params_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: RADIANCE_INPUT_BIND,
+                resource: wgpu::BindingResource::TextureView(&view(radiance_texture)),
+            },
+            wgpu::BindGroupEntry {
+                binding: DISPLAY_OUTPUT_BIND,
+                resource: wgpu::BindingResource::TextureView(&view(output_texture)),
+            },
+        ],
+        label: Some("Display Bind Group"),
+    })
+}
+
+fn create_display_pipeline(
+    device: &Device,
+    bind_group_layout: &BindGroupLayout,
+) -> ComputePipeline {
+    let display_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("display_shader.wgsl"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("display_shader.wgsl").into()),
+    });
+
+    let display_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Display Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Display Pipeline"),
+        layout: Some(&display_pipeline_layout),
+        module: &display_module,
+        entry_point: "main",
+        compilation_options: wgpu::PipelineCompilationOptions::default(),
+    })
+}
+
+fn create_display_bindgroup_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: DISPLAY_PARAMS_BIND,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            texture_layout_entry(RADIANCE_INPUT_BIND),
+            storage_texture_layout_entry(DISPLAY_OUTPUT_BIND, OUTPUT_FORMAT),
+        ],
+        label: Some("Display Bind Group Layout"),
+    })
+}
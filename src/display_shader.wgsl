@@ -0,0 +1,142 @@
+// turns the linear radiance of the path tracer or the denoiser into the displayed image,
+// it runs every frame, so the exposure and the tone mapper also change an image that is done accumulating
+
+struct DisplayParams {
+    exposure: f32,
+    tone_mapper: u32,
+};
+
+@group(0) @binding(0) var<uniform> display_params: DisplayParams;
+@group(0) @binding(1) var radiance_input: texture_2d<f32>;
+@group(0) @binding(2) var display_output: texture_storage_2d<rgba16float, write>;
+
+// tone mappers for the displayed image
+const TONE_MAP_CLAMP: u32 = 0u;
+const TONE_MAP_REINHARD: u32 = 1u;
+const TONE_MAP_ACES: u32 = 2u;
+const TONE_MAP_AGX: u32 = 3u;
+const TONE_MAP_PBR_NEUTRAL: u32 = 4u;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let size = vec2<i32>(textureDimensions(radiance_input));
+    let pixel = vec2<i32>(global_id.xy);
+
+    if pixel.x >= size.x || pixel.y >= size.y {
+        return;
+    }
+
+    let radiance: vec4<f32> = textureLoad(radiance_input, pixel, 0);
+
+    textureStore(display_output, pixel, display_color(radiance, display_params.exposure, display_params.tone_mapper));
+}
+
+fn display_color(radiance: vec4<f32>, exposure: f32, tone_mapper: u32) -> vec4<f32> {
+    // turns the linear radiance into the color that is shown on the screen, the sRGB encoding is done by the screen format
+    let exposed: vec3<f32> = max(radiance.rgb, vec3<f32>(0.0)) * exp2(exposure);
+
+    var mapped: vec3<f32>;
+
+    switch tone_mapper {
+        case TONE_MAP_REINHARD: {
+            mapped = tone_map_reinhard(exposed);
+        }
+        case TONE_MAP_ACES: {
+            mapped = tone_map_aces(exposed);
+        }
+        case TONE_MAP_AGX: {
+            mapped = tone_map_agx(exposed);
+        }
+        case TONE_MAP_PBR_NEUTRAL: {
+            mapped = tone_map_pbr_neutral(exposed);
+        }
+        default: {
+            mapped = exposed;
+        }
+    }
+
+    let display: vec3<f32> = clamp(mapped, vec3<f32>(0.0), vec3<f32>(1.0));
+
+    return vec4<f32>(display, clamp(radiance.a, 0.0, 1.0));
+}
+
+fn tone_map_reinhard(color: vec3<f32>) -> vec3<f32> {
+    // scales the color by its luminance so the hue stays the same
+    let luminance: f32 = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+
+    return color / (1.0 + luminance);
+}
+
+fn tone_map_aces(color: vec3<f32>) -> vec3<f32> {
+    // Stephen Hill's fit of the ACES reference rendering and output transforms
+    let input_matrix = mat3x3<f32>(
+        0.59719, 0.07600, 0.02840,
+        0.35458, 0.90834, 0.13383,
+        0.04823, 0.01566, 0.83777
+    );
+    let output_matrix = mat3x3<f32>(
+        1.60475, -0.10208, -0.00327,
+        -0.53108, 1.10813, -0.07276,
+        -0.07367, -0.00605, 1.07602
+    );
+
+    let v: vec3<f32> = input_matrix * color;
+    let a: vec3<f32> = v * (v + 0.0245786) - 0.000090537;
+    let b: vec3<f32> = v * (0.983729 * v + 0.4329510) + 0.238081;
+
+    return output_matrix * (a / b);
+}
+
+fn tone_map_agx(color: vec3<f32>) -> vec3<f32> {
+    // minimal AgX with the default contrast curve, the curve works on a log encoded color
+    let inset_matrix = mat3x3<f32>(
+        0.842479062253094, 0.0423282422610123, 0.0423756549057051,
+        0.0784335999999992, 0.878468636469772, 0.0784336,
+        0.0792237451477643, 0.0791661274605434, 0.879142973793104
+    );
+    let outset_matrix = mat3x3<f32>(
+        1.19687900512017, -0.0528968517574562, -0.0529716355144438,
+        -0.0980208811401368, 1.15190312990417, -0.0980434501171241,
+        -0.0990297440797205, -0.0989611768448433, 1.15107367264116
+    );
+
+    let min_ev: f32 = -12.47393;
+    let max_ev: f32 = 4.026069;
+
+    var x: vec3<f32> = inset_matrix * color;
+    x = clamp(log2(max(x, vec3<f32>(1e-10))), vec3<f32>(min_ev), vec3<f32>(max_ev));
+    x = (x - min_ev) / (max_ev - min_ev);
+
+    let x2: vec3<f32> = x * x;
+    let x4: vec3<f32> = x2 * x2;
+    x = 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x - 0.00232;
+
+    // the curve gives a display encoded color, it is made linear again for the sRGB encoding
+    x = outset_matrix * x;
+
+    return pow(max(x, vec3<f32>(0.0)), vec3<f32>(2.2));
+}
+
+fn tone_map_pbr_neutral(color: vec3<f32>) -> vec3<f32> {
+    // Khronos PBR Neutral, keeps the base colors of materials accurate under normal lighting
+    let start_compression: f32 = 0.8 - 0.04;
+    let desaturation: f32 = 0.15;
+
+    let x: f32 = min(color.r, min(color.g, color.b));
+    let offset: f32 = select(0.04, x - 6.25 * x * x, x < 0.08);
+    var result: vec3<f32> = color - offset;
+
+    let peak: f32 = max(result.r, max(result.g, result.b));
+
+    if peak < start_compression {
+        return result;
+    }
+
+    let d: f32 = 1.0 - start_compression;
+    let new_peak: f32 = 1.0 - d * d / (peak + d - start_compression);
+    result *= new_peak / peak;
+
+    let g: f32 = 1.0 - 1.0 / (desaturation * (peak - new_peak) + 1.0);
+
+    return mix(result, vec3<f32>(new_peak), g);
+}
@@ -0,0 +1,201 @@
+use super::buffers;
+use super::camera::Camera;
+use super::cli::Options;
+use super::define_scene::define_render_scene;
+use super::denoiser::DENOISE_REACH;
+use super::renderer::{Renderer, is_hdr_path, save_pixels};
+use super::{
+    check_scene_size, create_adapter, create_compute_pipeline, create_params,
+    generate_device_and_queue, generate_instance,
+};
+
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+use std::time::Instant;
+
+// samples per dispatch, higher than in the window because no UI has to stay responsive
+const COMPUTE_PER_FRAME: u32 = 16;
+
+// defaults when the command line does not set them
+const DEFAULT_SAMPLES: u32 = 256;
+const DEFAULT_OUTPUT: &str = "render.png";
+
+pub async fn render(options: Options) {
+    // renders the scene without a window or surface and saves the image,
+    // .exr and .pfm outputs get the linear radiance, other formats the tone mapped image
+    let size = PhysicalSize::new(options.width, options.height);
+    let samples = options.samples.unwrap_or(DEFAULT_SAMPLES);
+    let output = options.output.as_deref().unwrap_or(DEFAULT_OUTPUT);
+
+    let scene = define_render_scene(&options.scene_path);
+    check_scene_size(&scene);
+
+    let camera = Camera::new(size.width, size.height, &scene.camera);
+
+    let instance = generate_instance(options.backend);
+    let adapter = create_adapter(&instance, None, options.fallback_adapter).await;
+
+    println!("rendering on {}", adapter.get_info().name);
+
+    let (device, queue) = generate_device_and_queue(&adapter).await;
+
+    let mut tile_size = tile_size(size, options.tile_size, &device.limits());
+    let mut tiles = split_into_tiles(size, tile_size);
+
+    // the denoiser reads pixels around the edges of a tile, so every tile is rendered with a border
+    // of the neighbouring pixels that is cut off again, the bordered tile has to fit the limits too
+    let border = match options.denoise && tiles.len() > 1 {
+        true => DENOISE_REACH,
+        false => 0,
+    };
+
+    if border > 0 {
+        tile_size = PhysicalSize::new(
+            tile_size.width.saturating_sub(2 * border).max(8),
+            tile_size.height.saturating_sub(2 * border).max(8),
+        );
+        tiles = split_into_tiles(size, tile_size);
+    }
+
+    if tiles.len() > 1 {
+        println!("rendering in {} tiles", tiles.len());
+    }
+
+    // the pixel buffers on the gpu only hold one tile at a time
+    let (_, first_tile) = add_tile_border(tiles[0], border, size);
+    let mut params = create_params(&scene, size, COMPUTE_PER_FRAME, options.seed);
+    params.tile_width = first_tile.width;
+    params.tile_height = first_tile.height;
+
+    let (mut renderer, compute_bindgroup_layout, mut compute_bind_group) =
+        Renderer::new(camera, scene, &device, &queue, size, params);
+
+    renderer.denoise = options.denoise;
+
+    // the last dispatch of every tile only computes the samples that are still missing
+    renderer.sample_target = Some(samples);
+
+    let compute_pipeline =
+        create_compute_pipeline(&device, &compute_bindgroup_layout, &renderer.scene);
+
+    let start_time = Instant::now();
+    let mut reported_progress = 0;
+
+    let hdr = is_hdr_path(output);
+    let total_samples = samples * tiles.len() as u32;
+    let mut image = vec![[0.0; 4]; size.width as usize * size.height as usize];
+
+    for (tile_index, &(origin, tile)) in tiles.iter().enumerate() {
+        let (rendered_origin, rendered_tile) = add_tile_border((origin, tile), border, size);
+
+        if let Some(new_bind_group) =
+            renderer.set_tile(rendered_origin, rendered_tile, &compute_bindgroup_layout)
+        {
+            compute_bind_group = new_bind_group;
+        }
+
+        while renderer.sample_count() < samples {
+            renderer.compute_frame(&compute_pipeline, &compute_bind_group);
+
+            // waiting for every dispatch keeps the queue from growing faster than the gpu works
+            device.poll(wgpu::Maintain::Wait);
+
+            let rendered_samples = tile_index as u32 * samples + renderer.sample_count();
+            let progress = (rendered_samples * 10 / total_samples).min(10) * 10;
+
+            if progress > reported_progress {
+                reported_progress = progress;
+                println!("{}%", progress);
+            }
+        }
+
+        // the rows of the tile without its border are copied to their place in the whole image
+        let pixels = renderer.read_pixels(hdr);
+
+        let border_x = (origin.x - rendered_origin.x) as usize;
+        let border_y = (origin.y - rendered_origin.y) as usize;
+
+        for row in 0..tile.height as usize {
+            let source = (border_y + row) * rendered_tile.width as usize + border_x;
+            let start = (origin.y as usize + row) * size.width as usize + origin.x as usize;
+            image[start..start + tile.width as usize]
+                .copy_from_slice(&pixels[source..source + tile.width as usize]);
+        }
+    }
+
+    save_pixels(output, size.width, size.height, &image);
+
+    println!(
+        "{} samples rendered in {:.1} s to {}",
+        renderer.sample_count(),
+        start_time.elapsed().as_secs_f32(),
+        output
+    );
+}
+
+fn tile_size(
+    size: PhysicalSize<u32>,
+    requested: Option<u32>,
+    limits: &wgpu::Limits,
+) -> PhysicalSize<u32> {
+    // the f32 rgba accumulation buffer and the pixel textures of a tile have to fit the device limits
+    let (max_pixels, max_length) = buffers::pixel_buffer_limits(limits);
+
+    let fits = |width: u32, height: u32| {
+        width <= max_length && height <= max_length && width as u64 * height as u64 <= max_pixels
+    };
+
+    if let Some(length) = requested {
+        let length = length
+            .min(max_length)
+            .min((max_pixels as f64).sqrt() as u32);
+        return PhysicalSize::new(length.min(size.width), length.min(size.height));
+    }
+
+    if fits(size.width, size.height) {
+        return size;
+    }
+
+    // the largest square with a side that is a multiple of the workgroup size
+    let length = ((max_pixels as f64).sqrt() as u32).min(max_length) / 8 * 8;
+    PhysicalSize::new(length.min(size.width), length.min(size.height))
+}
+
+fn add_tile_border(
+    (origin, tile): (PhysicalPosition<u32>, PhysicalSize<u32>),
+    border: u32,
+    size: PhysicalSize<u32>,
+) -> (PhysicalPosition<u32>, PhysicalSize<u32>) {
+    // grows the tile by the border on every side, but not past the edges of the image
+    let x = origin.x.saturating_sub(border);
+    let y = origin.y.saturating_sub(border);
+    let right = (origin.x + tile.width + border).min(size.width);
+    let bottom = (origin.y + tile.height + border).min(size.height);
+
+    (
+        PhysicalPosition::new(x, y),
+        PhysicalSize::new(right - x, bottom - y),
+    )
+}
+
+fn split_into_tiles(
+    size: PhysicalSize<u32>,
+    tile_size: PhysicalSize<u32>,
+) -> Vec<(PhysicalPosition<u32>, PhysicalSize<u32>)> {
+    // rows of tiles from the top left, the last row and column are cut at the edge of the image
+    let mut tiles = Vec::new();
+
+    for y in (0..size.height).step_by(tile_size.height as usize) {
+        for x in (0..size.width).step_by(tile_size.width as usize) {
+            let width = tile_size.width.min(size.width - x);
+            let height = tile_size.height.min(size.height - y);
+
+            tiles.push((
+                PhysicalPosition::new(x, y),
+                PhysicalSize::new(width, height),
+            ));
+        }
+    }
+
+    tiles
+}
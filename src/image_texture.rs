@@ -1,3 +1,4 @@
+use glam::Vec3;
 use image::{GenericImageView, ImageBuffer, Rgba};
 
 #[derive(Debug, Clone)]
@@ -21,6 +22,36 @@ impl ImageTexture {
         }
     }
 
+    pub fn new_from_image_pixels(
+        path: &str,
+        texture_size: [u32; 2],
+        map_pixel: impl Fn(Rgba<u8>) -> Rgba<u8>,
+    ) -> ImageTexture {
+        // builds maps like masks out of an image, every pixel is replaced on its own
+        let mut image_buffer = load_png_image(path, texture_size);
+
+        for pixel in image_buffer.pixels_mut() {
+            *pixel = map_pixel(*pixel);
+        }
+
+        ImageTexture {
+            color: None,
+            image_buffer,
+        }
+    }
+
+    pub fn new_normal_map_from_image(
+        path: &str,
+        texture_size: [u32; 2],
+        strength: f32,
+    ) -> ImageTexture {
+        // the brightness of the image is taken as height, bright parts stand out of the surface
+        ImageTexture {
+            color: None,
+            image_buffer: normal_map_from_heights(&load_png_image(path, texture_size), strength),
+        }
+    }
+
     pub fn update_color(&mut self) {
         // we dont recolor textures that were loaded from files
         if let Some(color) = self.color {
@@ -64,3 +95,28 @@ pub fn load_png_image(path: &str, texture_size: [u32; 2]) -> ImageBuffer<Rgba<u8
     );
     img.to_rgba8()
 }
+
+pub fn normal_map_from_heights(
+    height_image: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    strength: f32,
+) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let (width, height) = height_image.dimensions();
+
+    // the textures repeat, so the neighbours of the edge pixels are on the other side
+    let height_at = |x: u32, y: u32| {
+        let Rgba([r, g, b, _]) = *height_image.get_pixel(x % width, y % height);
+        (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0
+    };
+
+    ImageBuffer::from_fn(width, height, |x, y| {
+        // central differences give the slope along the tangent (u) and the bitangent (v)
+        let slope_u = (height_at(x + 1, y) - height_at(x + width - 1, y)) * 0.5;
+        let slope_v = (height_at(x, y + 1) - height_at(x, y + height - 1)) * 0.5;
+
+        let normal = Vec3::new(-slope_u * strength, -slope_v * strength, 1.0).normalize();
+
+        // tangent space normals are stored as colors from 0 to 1
+        let encode = |value: f32| ((value * 0.5 + 0.5) * 255.0).round() as u8;
+        Rgba([encode(normal.x), encode(normal.y), encode(normal.z), 255])
+    })
+}
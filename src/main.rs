@@ -1,13 +1,23 @@
 mod buffers;
 mod camera;
+mod camera_path;
+mod cli;
+mod denoiser;
+mod display;
+mod headless;
 mod image_texture;
 mod renderer;
 mod triangle_object;
 
-use buffers::Params;
+use buffers::{
+    FILTER_BLACKMAN_HARRIS, FILTER_BOX, FILTER_GAUSSIAN, FILTER_TENT, LIGHT_DIRECTIONAL,
+    LIGHT_DISK, LIGHT_POINT, LIGHT_RECTANGLE, LIGHT_SPOT, NO_TEXTURE, PROJECTION_EQUIRECTANGULAR,
+    PROJECTION_FISHEYE, PROJECTION_ORTHOGRAPHIC, PROJECTION_PERSPECTIVE, Params, SceneLight,
+    TONE_MAP_ACES, TONE_MAP_AGX, TONE_MAP_CLAMP, TONE_MAP_PBR_NEUTRAL, TONE_MAP_REINHARD,
+};
 use camera::Camera;
 
-use renderer::Renderer;
+use renderer::{RenderScene, Renderer, get_emitter_data, light_capacity};
 
 mod define_scene;
 
@@ -19,13 +29,13 @@ use egui::{Color32, DragValue, Frame, FullOutput, pos2};
 
 use wgpu::{
     Adapter, Backends, BindGroup, BlendState, Device, Dx12Compiler, Gles3MinorVersion, Instance,
-    InstanceDescriptor, InstanceFlags, PipelineLayout, Queue, Surface, TextureDescriptor,
-    TextureDimension, TextureFormat, TextureUsages, include_wgsl,
+    InstanceDescriptor, InstanceFlags, PipelineLayout, Queue, Surface, TextureFormat,
+    TextureUsages, include_wgsl,
 };
 
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{ElementState, Event, KeyEvent, MouseButton, WindowEvent},
+    event::{ElementState, Event, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
     event_loop::EventLoop,
     keyboard::{KeyCode, PhysicalKey},
     window::{CursorGrabMode, Window},
@@ -36,33 +46,41 @@ use egui_winit_platform::{Platform, PlatformDescriptor};
 
 use std::time::Instant;
 
-pub const TRIANGLE_COUNT: u64 = 5552;
-pub const SUBOBJECT_COUNT: u64 = 802;
-pub const OBJECT_COUNT: u64 = 34;
+pub fn main() {
+    env_logger::init();
 
-pub const SPHERE_COUNT: u64 = 3;
-pub const MATERIAL_COUNT: u64 = 19;
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            // --help gives an empty error and is not a failure
+            if !error.is_empty() {
+                println!("{}", error);
+            }
+            println!("{}", cli::USAGE);
+            std::process::exit(if error.is_empty() { 0 } else { 1 });
+        }
+    };
+
+    if options.headless {
+        pollster::block_on(headless::render(options));
+        return;
+    }
 
-pub fn main() {
     let event_loop = EventLoop::new().expect("failed to make eventloop");
 
     let builder = winit::window::WindowBuilder::new();
 
-    // window width is set at 1600, because GPU buffer requires n * 256 bytes (n * 64 pixels * 4*u8 colors ) for every horisontal row,
-    // changing it to not be a multiple of 64 requires implementing buffer values when getting colors from the GPU
-    let window_size = PhysicalSize::new(1600, 900);
+    let window_size = PhysicalSize::new(options.width, options.height);
 
     let window = builder
         .with_inner_size(window_size)
         .build(&event_loop)
         .expect("failed to make window");
 
-    window.set_resizable(false);
-    env_logger::init();
-    pollster::block_on(run(event_loop, window));
+    pollster::block_on(run(event_loop, window, options));
 }
 
-async fn run(event_loop: EventLoop<()>, window: Window) {
+async fn run(event_loop: EventLoop<()>, window: Window, options: cli::Options) {
     let mut movement_mode = false;
 
     let mut size = window.inner_size();
@@ -79,9 +97,7 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
     let mut compute_counter: u32 = 0;
     let mut compute_per_second: u32 = 0;
 
-    let camera = Camera::new(size.width, size.height);
-
-    let scene: renderer::RenderScene = define_render_scene();
+    let scene: RenderScene = define_render_scene(&options.scene_path);
 
     let mut last_mouse_pos: egui::Pos2 = pos2(0., 0.);
 
@@ -91,99 +107,43 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
 
     let computation_per_frame = 5;
 
-    let instance = generate_instance();
+    let instance = generate_instance(options.backend);
 
     let surface: Surface = instance
         .create_surface(&window)
         .expect("failed to make a surface");
-    let adapter = create_adapter(&instance, &surface).await;
+    let adapter = create_adapter(&instance, Some(&surface), false).await;
     // Create the logical device and command queue
     let (device, queue) = generate_device_and_queue(&adapter).await;
 
-    let triangle_count = scene
-        .objects
-        .iter()
-        .map(|obj: &SceneObject| obj.object_triangles.len())
-        .sum::<usize>() as u32;
-
-    let sub_object_count = scene
-        .objects
-        .iter()
-        .map(|obj: &SceneObject| obj.sub_object_info.len())
-        .sum::<usize>() as u32;
-
-    println!("the following numbers should be the same in the compute shader for the buffer sizes");
-    dbg!(triangle_count);
-    dbg!(sub_object_count);
-    dbg!(scene.objects.len());
+    check_scene_size(&scene);
 
-    dbg!(scene.spheres.len());
-    dbg!(scene.materials.len());
+    // a window too large for the pixel buffers of the device starts at a lower resolution
+    let render_size = buffers::fit_pixel_buffer_limits(size, &device.limits());
 
-    assert_eq!(triangle_count, TRIANGLE_COUNT as u32);
-    assert_eq!(sub_object_count, SUBOBJECT_COUNT as u32);
-    assert_eq!(scene.objects.len(), OBJECT_COUNT as usize);
+    let camera = Camera::new(render_size.width, render_size.height, &scene.camera);
 
-    assert_eq!(scene.spheres.len(), SPHERE_COUNT as usize);
-    assert_eq!(scene.materials.len(), MATERIAL_COUNT as usize);
-
-    // Create uniform buffer
-    let params = Params {
-        screen_width: size.width,
-        accumulation_index: 1,
-        accumulate: 1,
-        sphere_count: scene.spheres.len() as u32,
-        object_count: scene.objects.len() as u32,
-        compute_per_frame: computation_per_frame,
-        texture_width: scene.texture_size[0],
-        texture_height: scene.texture_size[1],
-        textue_count: scene.image_textures.len() as u32,
-        env_map_width: scene.env_map_size[0],
-        env_map_height: scene.env_map_size[1],
-        _padding: [0; 4],
-    };
+    let params = create_params(&scene, render_size, computation_per_frame, options.seed);
 
-    let (mut scene_renderer, compute_bindgroup_layout, compute_bind_group) =
+    let (mut scene_renderer, compute_bindgroup_layout, mut compute_bind_group) =
         Renderer::new(camera, scene, &device, &queue, size, params);
 
-    // ################################ GPU COMPUTE PIPELINE #########################################
-
-    // compute shader compile time arguments
-    let compute_shader_code = include_str!("compute_shader.wgsl")
-        .replace("TRIANGLE_COUNT_PLACEHOLDER", &TRIANGLE_COUNT.to_string())
-        .replace("SUBOBJECT_COUNT_PLACEHOLDER", &SUBOBJECT_COUNT.to_string())
-        .replace("OBJECT_COUNT_PLACEHOLDER", &OBJECT_COUNT.to_string())
-        .replace("SPHERE_COUNT_PLACEHOLDER", &SPHERE_COUNT.to_string())
-        .replace("MATERIAL_COUNT_PLACEHOLDER", &MATERIAL_COUNT.to_string());
+    scene_renderer.sample_target = options.samples;
+    scene_renderer.output_path = options.output;
+    scene_renderer.denoise = options.denoise;
 
-    let compute_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("compute_shader.wgsl"),
-        source: wgpu::ShaderSource::Wgsl(compute_shader_code.into()),
-    });
-
-    let compute_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label: Some("Compute Pipeline Layout"),
-        bind_group_layouts: &[&compute_bindgroup_layout],
-        push_constant_ranges: &[],
-    });
+    // ################################ GPU COMPUTE PIPELINE #########################################
 
-    let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-        label: Some("Compute Pipeline"),
-        layout: Some(&compute_pipeline_layout),
-        module: &compute_module,
-        entry_point: "main",
-        compilation_options: wgpu::PipelineCompilationOptions::default(),
-    });
+    let compute_pipeline =
+        create_compute_pipeline(&device, &compute_bindgroup_layout, &scene_renderer.scene);
 
     // #####################################################################################
     // ################################ RENDER PIPELINE #########################################
 
-    let mut texture = create_texture(&device, size);
-
     let sampler: wgpu::Sampler = generate_sampler(&device);
 
     let (mut bind_group_layout, mut bind_group) =
-        create_device_bindgroup(&device, &texture, &sampler);
+        create_device_bindgroup(&device, scene_renderer.output_texture(), &sampler);
 
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: None,
@@ -191,17 +151,25 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
         push_constant_ranges: &[],
     });
 
-    let render_pipeline = create_render_pipeline(&device, &pipeline_layout, texture.format());
+    // the output texture is linear, an sRGB surface encodes the colors when they are drawn
+    let surface_format = surface
+        .get_capabilities(&adapter)
+        .formats
+        .into_iter()
+        .find(TextureFormat::is_srgb)
+        .unwrap_or(TextureFormat::Bgra8UnormSrgb);
+
+    let render_pipeline = create_render_pipeline(&device, &pipeline_layout, surface_format);
 
     let mut surface_config = wgpu::SurfaceConfiguration {
         usage: TextureUsages::RENDER_ATTACHMENT,
-        format: texture.format(),
+        format: surface_format,
         width: size.width,
         height: size.height,
         present_mode: wgpu::PresentMode::Immediate,
         desired_maximum_frame_latency: 2,
         alpha_mode: wgpu::CompositeAlphaMode::Auto,
-        view_formats: vec![texture.format()],
+        view_formats: vec![surface_format],
     };
 
     surface.configure(&device, &surface_config);
@@ -271,12 +239,14 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
                                 (size.height as f32 / 2.).round(),
                             );
 
-                            scene_renderer.on_resize(&size);
+                            compute_bind_group =
+                                scene_renderer.on_resize(&size, &compute_bindgroup_layout);
 
-                            texture = create_texture(&device, size);
-
-                            (bind_group_layout, bind_group) =
-                                create_device_bindgroup(&device, &texture, &sampler);
+                            (bind_group_layout, bind_group) = create_device_bindgroup(
+                                &device,
+                                scene_renderer.output_texture(),
+                                &sampler,
+                            );
 
                             surface.configure(&device, &surface_config);
 
@@ -302,6 +272,58 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
                             window.request_redraw();
                         }
 
+                        WindowEvent::MouseWheel { delta, .. } if movement_mode => {
+                            // scrolling while flying changes the movement speed
+                            let scroll = match delta {
+                                MouseScrollDelta::LineDelta(_, y) => y,
+                                MouseScrollDelta::PixelDelta(position) => position.y as f32 / 50.0,
+                            };
+
+                            let camera = &mut scene_renderer.camera;
+                            camera.movement_speed =
+                                (camera.movement_speed * 1.1_f32.powf(scroll)).clamp(0.01, 50.0);
+                        }
+
+                        WindowEvent::MouseWheel { delta, .. }
+                            if scene_renderer.orbit_mode
+                                && !platform.context().is_pointer_over_area() =>
+                        {
+                            // scrolling in orbit mode zooms towards the orbit center
+                            let scroll = match delta {
+                                MouseScrollDelta::LineDelta(_, y) => y,
+                                MouseScrollDelta::PixelDelta(position) => position.y as f32 / 50.0,
+                            };
+
+                            scene_renderer.on_orbit(egui::Vec2::ZERO, egui::Vec2::ZERO, scroll);
+                            window.request_redraw();
+                        }
+
+                        WindowEvent::MouseInput {
+                            state: ElementState::Pressed,
+                            button: MouseButton::Left,
+                            ..
+                        } => {
+                            // ctrl + click on the scene picks the focus distance
+                            let context = platform.context();
+
+                            let focus_pos = context
+                                .input(|i: &egui::InputState| {
+                                    i.modifiers.ctrl.then_some(i.pointer.hover_pos())
+                                })
+                                .flatten()
+                                .filter(|_| !context.is_pointer_over_area());
+
+                            if let Some(pos) = focus_pos {
+                                let scale_factor = window.scale_factor() as f32;
+
+                                if scene_renderer
+                                    .focus_on_pixel(pos.x * scale_factor, pos.y * scale_factor)
+                                {
+                                    window.request_redraw();
+                                }
+                            }
+                        }
+
                         WindowEvent::MouseInput {
                             state,
                             button: MouseButton::Right,
@@ -367,8 +389,24 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
                             {
                                 compute_timer = Instant::now();
                                 compute_counter += computation_per_frame;
+
+                                // a new render scale changes the size of the accumulation buffer and the output texture
+                                if let Some(new_bind_group) =
+                                    scene_renderer.update_render_scale(&compute_bindgroup_layout)
+                                {
+                                    compute_bind_group = new_bind_group;
+
+                                    (bind_group_layout, bind_group) = create_device_bindgroup(
+                                        &device,
+                                        scene_renderer.output_texture(),
+                                        &sampler,
+                                    );
+                                }
+
                                 scene_renderer
                                     .compute_frame(&compute_pipeline, &compute_bind_group);
+
+                                scene_renderer.update_camera_path();
                             }
 
                             if fps_timer.elapsed().as_millis() > frametime_target {
@@ -387,8 +425,6 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
                                     },
                                 );
 
-                                scene_renderer.update_texture(&mut encoder, &texture);
-
                                 // #############################################################################################
 
                                 //println!(
@@ -472,14 +508,67 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
                                     let delta = current_mouse_pos - mouse_resting_position;
 
                                     scene_renderer.on_update(delta, &platform.context());
+                                } else if scene_renderer.orbit_mode {
+                                    // dragging on the scene orbits, the middle mouse button pans
+                                    let context = platform.context();
+
+                                    let on_scene = !context.is_pointer_over_area()
+                                        && !context.is_using_pointer();
+
+                                    let (drag_delta, pan_delta) =
+                                        context.input(|i: &egui::InputState| {
+                                            let delta = i.pointer.delta();
+                                            let dragging =
+                                                i.pointer.primary_down() && !i.modifiers.ctrl;
+                                            let panning = i.pointer.middle_down();
+
+                                            (
+                                                if dragging { delta } else { egui::Vec2::ZERO },
+                                                if panning { delta } else { egui::Vec2::ZERO },
+                                            )
+                                        });
+
+                                    if on_scene {
+                                        // egui measures in points, the camera works in physical pixels
+                                        let scale_factor = window.scale_factor() as f32;
+                                        scene_renderer.on_orbit(
+                                            drag_delta * scale_factor,
+                                            pan_delta * scale_factor,
+                                            0.0,
+                                        );
+                                    }
                                 }
 
+                                scene_renderer.update_turntable();
+
                                 if platform
                                     .context()
                                     .input(|i: &egui::InputState| i.key_pressed(egui::Key::F11))
                                 {
                                     show_ui = !show_ui;
                                 }
+
+                                if platform
+                                    .context()
+                                    .input(|i: &egui::InputState| i.key_pressed(egui::Key::F12))
+                                {
+                                    scene_renderer.save_screenshot();
+                                }
+
+                                // number keys fly to the camera bookmarks
+                                let context = platform.context();
+
+                                let bookmark_key = context.input(|i: &egui::InputState| {
+                                    BOOKMARK_KEYS.iter().position(|key| i.key_pressed(*key))
+                                });
+
+                                if let Some(index) = bookmark_key
+                                    && !context.wants_keyboard_input()
+                                {
+                                    scene_renderer.go_to_bookmark(index);
+                                }
+
+                                scene_renderer.update_camera_transition();
                             }
                         }
 
@@ -501,23 +590,126 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
     drop(queue);
 }
 
-fn create_texture(device: &wgpu::Device, size: winit::dpi::PhysicalSize<u32>) -> wgpu::Texture {
-    let texture_size = wgpu::Extent3d {
-        width: size.width.max(1),
-        height: size.height.max(1),
-        depth_or_array_layers: 1,
-    };
+fn scene_counts(scene: &RenderScene) -> [(&'static str, &'static str, usize); 6] {
+    // placeholder in the compute shader, name and size of every scene array with a fixed size in the shader,
+    // the sub objects come before the objects so their placeholder is replaced first
+    let triangle_count = scene
+        .objects
+        .iter()
+        .map(|obj: &SceneObject| obj.object_triangles.len())
+        .sum::<usize>();
 
-    device.create_texture(&TextureDescriptor {
-        label: None,
-        size: texture_size,
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: TextureDimension::D2,
-        format: TextureFormat::Rgba8UnormSrgb,
-        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
-
-        view_formats: &[],
+    let sub_object_count = scene
+        .objects
+        .iter()
+        .map(|obj: &SceneObject| obj.sub_object_info.len())
+        .sum::<usize>();
+
+    [
+        ("TRIANGLE_COUNT_PLACEHOLDER", "triangles", triangle_count),
+        (
+            "SUBOBJECT_COUNT_PLACEHOLDER",
+            "sub objects",
+            sub_object_count,
+        ),
+        ("OBJECT_COUNT_PLACEHOLDER", "objects", scene.objects.len()),
+        ("SPHERE_COUNT_PLACEHOLDER", "spheres", scene.spheres.len()),
+        (
+            "MATERIAL_COUNT_PLACEHOLDER",
+            "materials",
+            scene.materials.len(),
+        ),
+        (
+            "LIGHT_COUNT_PLACEHOLDER",
+            "lights",
+            light_capacity(&scene.lights),
+        ),
+    ]
+}
+
+fn check_scene_size(scene: &RenderScene) {
+    // the array sizes of the compute shader come from the loaded scene, but WGSL arrays can not be empty,
+    // the light array always has free slots so a scene without lights still loads
+    let empty_arrays: Vec<&str> = scene_counts(scene)
+        .into_iter()
+        .filter(|(_, _, count)| *count == 0)
+        .map(|(_, name, _)| name)
+        .collect();
+
+    if !empty_arrays.is_empty() {
+        println!(
+            "the scene has no {}, it needs at least one of each",
+            empty_arrays.join(", ")
+        );
+        std::process::exit(1);
+    }
+}
+
+fn create_params(
+    scene: &RenderScene,
+    size: PhysicalSize<u32>,
+    computation_per_frame: u32,
+    seed: u32,
+) -> Params {
+    // starting values of the uniform buffer
+    Params {
+        screen_width: size.width,
+        accumulated_samples: 0,
+        accumulate: 1,
+        sphere_count: scene.spheres.len() as u32,
+        object_count: scene.objects.len() as u32,
+        compute_per_frame: computation_per_frame,
+        texture_width: scene.texture_size[0],
+        texture_height: scene.texture_size[1],
+        textue_count: scene.image_textures.len() as u32,
+        env_map_width: scene.env_map_size[0],
+        env_map_height: scene.env_map_size[1],
+        emitter_count: get_emitter_data(scene).len() as u32,
+        light_count: scene.lights.len() as u32,
+        max_diffuse_bounces: 8,
+        max_specular_bounces: 8,
+        max_transmission_bounces: 16,
+        roulette_depth: 3,
+        screen_height: size.height,
+        filter_type: FILTER_BLACKMAN_HARRIS,
+        filter_radius: 1.5,
+        motion_blur: 1,
+        seed,
+        tile_x: 0,
+        tile_y: 0,
+        tile_width: size.width,
+        tile_height: size.height,
+    }
+}
+
+fn create_compute_pipeline(
+    device: &wgpu::Device,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    scene: &RenderScene,
+) -> wgpu::ComputePipeline {
+    // compute shader compile time arguments, the array sizes of the loaded scene
+    let compute_shader_code = scene_counts(scene).into_iter().fold(
+        include_str!("compute_shader.wgsl").to_string(),
+        |code, (placeholder, _, count)| code.replace(placeholder, &count.to_string()),
+    );
+
+    let compute_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("compute_shader.wgsl"),
+        source: wgpu::ShaderSource::Wgsl(compute_shader_code.into()),
+    });
+
+    let compute_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Compute Pipeline Layout"),
+        bind_group_layouts: &[bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Compute Pipeline"),
+        layout: Some(&compute_pipeline_layout),
+        module: &compute_module,
+        entry_point: "main",
+        compilation_options: wgpu::PipelineCompilationOptions::default(),
     })
 }
 
@@ -633,13 +825,18 @@ fn create_render_pipeline(
     render_pipeline
 }
 
-async fn create_adapter(instance: &wgpu::Instance, surface: &Surface<'_>) -> wgpu::Adapter {
+async fn create_adapter(
+    instance: &wgpu::Instance,
+    surface: Option<&Surface<'_>>,
+    force_fallback_adapter: bool,
+) -> wgpu::Adapter {
     instance
         .request_adapter(&wgpu::RequestAdapterOptions {
             power_preference: wgpu::PowerPreference::HighPerformance,
-            force_fallback_adapter: false,
-            // Request an adapter which can render to our surface
-            compatible_surface: Some(surface),
+            // the fallback adapter is a software renderer for machines without a gpu
+            force_fallback_adapter,
+            // Request an adapter which can render to our surface, headless rendering has none
+            compatible_surface: surface,
         })
         .await
         .expect("Failed to find an appropriate adapter")
@@ -647,7 +844,7 @@ async fn create_adapter(instance: &wgpu::Instance, surface: &Surface<'_>) -> wgp
 
 async fn generate_device_and_queue(adapter: &Adapter) -> (Device, Queue) {
     let adapter_limits = wgpu::Limits {
-        max_storage_buffers_per_shader_stage: 6,
+        max_storage_buffers_per_shader_stage: 7,
         ..wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits())
     };
     adapter
@@ -676,9 +873,9 @@ fn generate_sampler(device: &wgpu::Device) -> wgpu::Sampler {
     })
 }
 
-fn generate_instance() -> Instance {
+fn generate_instance(backends: Backends) -> Instance {
     let instance_desc: wgpu::InstanceDescriptor = InstanceDescriptor {
-        backends: Backends::VULKAN,
+        backends,
         flags: InstanceFlags::default(),
         dx12_shader_compiler: Dx12Compiler::default(),
         gles_minor_version: Gles3MinorVersion::default(),
@@ -689,6 +886,19 @@ fn generate_instance() -> Instance {
 
 // ######################### UI CREATION ########################################
 
+// number keys that fly to the bookmarks, the first key goes to the first bookmark
+const BOOKMARK_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
 // simple macro for makíng the UI more compact
 macro_rules! create_drag_value {
     ($ui:expr, $value:expr, $speed:expr, $range:expr, $prefix:expr) => {{
@@ -734,6 +944,24 @@ fn create_ui(
 
             ui.label(format!("fps: {}", compute_per_second));
 
+            ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                if ui
+                    .button("save screenshot")
+                    .on_hover_text("F12, saved in the screenshots folder")
+                    .clicked()
+                {
+                    screne_renderer.save_screenshot();
+                }
+
+                if ui
+                    .button("export HDR")
+                    .on_hover_text("linear EXR and PFM of the accumulated light")
+                    .clicked()
+                {
+                    screne_renderer.export_hdr_image();
+                }
+            });
+
             ui.vertical_centered(|ui| {
                 let sky_color = &mut screne_renderer.scene.environment_map.color;
                 if let Some(sky_color) = sky_color {
@@ -747,24 +975,380 @@ fn create_ui(
                     };
                 }
 
+                // a path render waits for the samples of every frame, it needs the accumulation
+                let rendering_path = screne_renderer.path_render_progress().is_some();
+
                 if ui
-                    .checkbox(&mut screne_renderer.accumulate, "light accumulation")
+                    .add_enabled(
+                        !rendering_path,
+                        egui::Checkbox::new(&mut screne_renderer.accumulate, "light accumulation"),
+                    )
+                    .on_disabled_hover_text("stays on while the camera path is rendered")
                     .changed()
                 {
                     interacted = true;
                 };
 
+                ui.label(format!("samples: {}", screne_renderer.sample_count()));
+
+                ui.checkbox(&mut screne_renderer.denoise, "denoise")
+                    .on_hover_text("edge-avoiding filter guided by the albedo, normals and depth, also used for saved images");
+
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    let render_size = screne_renderer.render_size();
+                    ui.label(format!(
+                        "render size: {} x {}",
+                        render_size.width, render_size.height
+                    ));
+
+                    let mut render_percent = screne_renderer.render_scale * 100.0;
+
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut render_percent, 25.0..=200.0)
+                                .suffix("%")
+                                .integer(),
+                        )
+                        .changed()
+                    {
+                        screne_renderer.render_scale = render_percent / 100.0;
+                    }
+
+                    ui.checkbox(&mut screne_renderer.auto_render_scale, "lower while moving")
+                        .on_hover_text("half the render scale while the camera moves");
+                });
+
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    ui.label("max bounces:");
+
+                    let diffuse_bounces = &mut screne_renderer.max_diffuse_bounces;
+
+                    if create_drag_value!(ui, diffuse_bounces, 0.1, 0..=64, "diffuse: ") {
+                        interacted = true;
+                    }
+
+                    let specular_bounces = &mut screne_renderer.max_specular_bounces;
+
+                    if create_drag_value!(ui, specular_bounces, 0.1, 0..=64, "specular: ") {
+                        interacted = true;
+                    }
+
+                    let transmission_bounces = &mut screne_renderer.max_transmission_bounces;
+
+                    if create_drag_value!(ui, transmission_bounces, 0.1, 0..=64, "transmission: ") {
+                        interacted = true;
+                    }
+
+                    let roulette_depth = &mut screne_renderer.roulette_depth;
+
+                    if create_drag_value!(ui, roulette_depth, 0.1, 0..=64, "roulette after: ") {
+                        interacted = true;
+                    }
+                });
+
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    ui.label("pixel filter:");
+
+                    let filter_types = [
+                        (FILTER_BOX, "box"),
+                        (FILTER_TENT, "tent"),
+                        (FILTER_GAUSSIAN, "gaussian"),
+                        (FILTER_BLACKMAN_HARRIS, "blackman-harris"),
+                    ];
+
+                    let selected_name = filter_types
+                        .iter()
+                        .find(|(filter_type, _)| *filter_type == screne_renderer.filter_type)
+                        .map_or("unknown", |(_, name)| name);
+
+                    egui::ComboBox::from_id_source("filter_type")
+                        .selected_text(selected_name)
+                        .show_ui(ui, |ui| {
+                            for (filter_type, name) in filter_types {
+                                if ui
+                                    .selectable_value(
+                                        &mut screne_renderer.filter_type,
+                                        filter_type,
+                                        name,
+                                    )
+                                    .changed()
+                                {
+                                    interacted = true;
+                                }
+                            }
+                        });
+
+                    let filter_radius = &mut screne_renderer.filter_radius;
+
+                    if create_drag_value!(ui, filter_radius, 0.01, 0.5..=4.0, "radius: ") {
+                        interacted = true;
+                    }
+                });
+
+                // display settings only change how the samples are shown, the accumulation keeps going
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    ui.label("display:");
+
+                    let exposure = &mut screne_renderer.exposure;
+                    create_drag_value!(ui, exposure, 0.05, -10.0..=10.0, "exposure EV: ");
+
+                    let tone_mappers = [
+                        (TONE_MAP_CLAMP, "clamp"),
+                        (TONE_MAP_REINHARD, "reinhard"),
+                        (TONE_MAP_ACES, "ACES filmic"),
+                        (TONE_MAP_AGX, "AgX"),
+                        (TONE_MAP_PBR_NEUTRAL, "PBR neutral"),
+                    ];
+
+                    let selected_name = tone_mappers
+                        .iter()
+                        .find(|(tone_mapper, _)| *tone_mapper == screne_renderer.tone_mapper)
+                        .map_or("unknown", |(_, name)| name);
+
+                    egui::ComboBox::from_id_source("tone_mapper")
+                        .selected_text(selected_name)
+                        .show_ui(ui, |ui| {
+                            for (tone_mapper, name) in tone_mappers {
+                                ui.selectable_value(
+                                    &mut screne_renderer.tone_mapper,
+                                    tone_mapper,
+                                    name,
+                                );
+                            }
+                        });
+                });
+
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    ui.label("bookmarks:");
+
+                    let mut selected_bookmark = None;
+
+                    for (i, bookmark) in screne_renderer.scene.camera_bookmarks.iter().enumerate() {
+                        if ui.button(format!("{}: {}", i + 1, bookmark.name)).clicked() {
+                            selected_bookmark = Some(i);
+                        }
+                    }
+
+                    if let Some(index) = selected_bookmark {
+                        screne_renderer.go_to_bookmark(index);
+                    }
+
+                    if ui.button("save current view").clicked() {
+                        let bookmarks = &mut screne_renderer.scene.camera_bookmarks;
+                        let name = format!("view {}", bookmarks.len() + 1);
+
+                        bookmarks.push(screne_renderer.camera.bookmark(name));
+                    }
+                });
+
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    ui_camera_path(screne_renderer, ui);
+                });
+
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    ui.label("camera:");
+
+                    let camera = &mut screne_renderer.camera;
+
+                    if create_drag_value!(ui, &mut camera.vertical_fov, 0.1, 1.0..=179.0, "fov: ") {
+                        interacted = true;
+                    }
+
+                    let far_clip = camera.far_clip;
+
+                    if create_drag_value!(
+                        ui,
+                        &mut camera.near_clip,
+                        0.01,
+                        0.01..=far_clip,
+                        "near clip: "
+                    ) {
+                        interacted = true;
+                    }
+
+                    let near_clip = camera.near_clip;
+
+                    if create_drag_value!(
+                        ui,
+                        &mut camera.far_clip,
+                        0.5,
+                        near_clip..=10000.0,
+                        "far clip: "
+                    ) {
+                        interacted = true;
+                    }
+
+                    // speeds only change how the camera moves, the image stays the same
+                    create_drag_value!(
+                        ui,
+                        &mut camera.movement_speed,
+                        0.01,
+                        0.01..=50.0,
+                        "move speed: "
+                    );
+
+                    create_drag_value!(
+                        ui,
+                        &mut camera.turning_speed,
+                        0.0001,
+                        0.0001..=0.01,
+                        "turn speed: "
+                    );
+                });
+
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    ui.label("orbit:");
+
+                    let mut orbit_mode = screne_renderer.orbit_mode;
+
+                    if ui
+                        .checkbox(&mut orbit_mode, "orbit selected object")
+                        .on_hover_text("drag to rotate, scroll to zoom, middle mouse to pan")
+                        .changed()
+                    {
+                        if orbit_mode {
+                            screne_renderer.start_orbit();
+                        } else {
+                            screne_renderer.orbit_mode = false;
+                        }
+                    }
+
+                    ui.add_enabled_ui(orbit_mode, |ui: &mut egui::Ui| {
+                        if ui.button("recenter").clicked() {
+                            screne_renderer.start_orbit();
+                        }
+
+                        ui.checkbox(&mut screne_renderer.turntable, "turntable");
+
+                        create_drag_value!(
+                            ui,
+                            &mut screne_renderer.turntable_speed,
+                            0.5,
+                            -360.0..=360.0,
+                            "degrees per second: "
+                        );
+                    });
+                });
+
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    ui.label("projection:");
+
+                    let projection_types = [
+                        (PROJECTION_PERSPECTIVE, "perspective"),
+                        (PROJECTION_ORTHOGRAPHIC, "orthographic"),
+                        (PROJECTION_EQUIRECTANGULAR, "equirectangular"),
+                        (PROJECTION_FISHEYE, "fisheye"),
+                    ];
+
+                    let camera = &mut screne_renderer.camera;
+
+                    let selected_name = projection_types
+                        .iter()
+                        .find(|(projection_type, _)| *projection_type == camera.projection_type)
+                        .map_or("unknown", |(_, name)| name);
+
+                    egui::ComboBox::from_id_source("projection_type")
+                        .selected_text(selected_name)
+                        .show_ui(ui, |ui| {
+                            for (projection_type, name) in projection_types {
+                                if ui
+                                    .selectable_value(
+                                        &mut camera.projection_type,
+                                        projection_type,
+                                        name,
+                                    )
+                                    .changed()
+                                {
+                                    interacted = true;
+                                }
+                            }
+                        });
+
+                    if camera.projection_type == PROJECTION_ORTHOGRAPHIC {
+                        let height = &mut camera.orthographic_height;
+
+                        if create_drag_value!(ui, height, 0.1, 0.1..=400.0, "view height: ") {
+                            interacted = true;
+                        }
+                    }
+
+                    if camera.projection_type == PROJECTION_FISHEYE {
+                        let fisheye_fov = &mut camera.fisheye_fov;
+
+                        if create_drag_value!(ui, fisheye_fov, 1.0, 10.0..=360.0, "fov: ") {
+                            interacted = true;
+                        }
+                    }
+                });
+
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    ui.label("depth of field:");
+
+                    let aperture_radius = &mut screne_renderer.camera.aperture_radius;
+
+                    if create_drag_value!(ui, aperture_radius, 0.01, 0.0..=5.0, "aperture: ") {
+                        interacted = true;
+                    }
+
+                    let focus_distance = &mut screne_renderer.camera.focus_distance;
+
+                    if create_drag_value!(ui, focus_distance, 0.1, 0.1..=400.0, "focus: ") {
+                        interacted = true;
+                    }
+
+                    let aperture_blades = &mut screne_renderer.camera.aperture_blades;
+
+                    if create_drag_value!(ui, aperture_blades, 0.1, 0..=12, "blades: ") {
+                        interacted = true;
+                    }
+
+                    ui.label("ctrl + click to focus");
+                });
+
+                ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+                    if ui
+                        .checkbox(&mut screne_renderer.motion_blur, "motion blur")
+                        .changed()
+                    {
+                        interacted = true;
+                    }
+
+                    let camera = &mut screne_renderer.camera;
+
+                    // the current pose becomes the end of the shutter, the camera can then move to the start
+                    if ui
+                        .button("set camera end")
+                        .on_hover_text("camera pose when the shutter closes")
+                        .clicked()
+                    {
+                        camera.motion_end = Some((camera.position, camera.direction));
+                        interacted = true;
+                    }
+
+                    if camera.motion_end.is_some() && ui.button("clear camera end").clicked() {
+                        camera.motion_end = None;
+                        interacted = true;
+                    }
+                });
+
                 ui.add_space(10.0);
 
                 ui.vertical_centered_justified(|ui: &mut egui::Ui| {
                     ui.label("selected object:");
-                    ui.add(
-                        egui::Slider::new(
-                            &mut screne_renderer.object_index,
-                            0..=(screne_renderer.scene.objects.len() - 1),
+                    let object_changed = ui
+                        .add(
+                            egui::Slider::new(
+                                &mut screne_renderer.object_index,
+                                0..=(screne_renderer.scene.objects.len() - 1),
+                            )
+                            .integer(),
                         )
-                        .integer(),
-                    );
+                        .changed();
+
+                    // the orbit follows the selected object
+                    if object_changed && screne_renderer.orbit_mode {
+                        screne_renderer.start_orbit();
+                    }
 
                     let current_object =
                         &mut screne_renderer.scene.objects[screne_renderer.object_index];
@@ -805,6 +1389,60 @@ fn create_ui(
                         }
                     });
 
+                    ui.add_space(10.0);
+
+                    let motion = &mut current_object.motion_transformation;
+
+                    ui.label("motion:");
+                    ui.horizontal(|ui| {
+                        if create_drag_value!(ui, &mut motion[0], 0.01, -20.0..=20.0, "X: ") {
+                            interacted = true;
+                        }
+
+                        if create_drag_value!(ui, &mut motion[1], 0.01, -20.0..=20.0, "Y: ") {
+                            interacted = true;
+                        }
+
+                        if create_drag_value!(ui, &mut motion[2], 0.01, -20.0..=20.0, "Z: ") {
+                            interacted = true;
+                        }
+                    });
+
+                    let motion_rotation = &mut current_object.motion_rotation;
+
+                    ui.label("motion rotation:");
+                    ui.horizontal(|ui| {
+                        if create_drag_value!(
+                            ui,
+                            &mut motion_rotation[0],
+                            0.1,
+                            -180.0..=180.0,
+                            "X: "
+                        ) {
+                            interacted = true;
+                        }
+
+                        if create_drag_value!(
+                            ui,
+                            &mut motion_rotation[1],
+                            0.1,
+                            -180.0..=180.0,
+                            "Y: "
+                        ) {
+                            interacted = true;
+                        }
+
+                        if create_drag_value!(
+                            ui,
+                            &mut motion_rotation[2],
+                            0.1,
+                            -180.0..=180.0,
+                            "Z: "
+                        ) {
+                            interacted = true;
+                        }
+                    });
+
                     // sliders for scale
                     ui.vertical_centered_justified(|ui: &mut egui::Ui| {
                         let object_size = &mut current_object.scale;
@@ -875,6 +1513,10 @@ fn create_ui(
 
                 let material_index: usize = current_sphere.material_index as usize;
                 ui_material_selection(screne_renderer, material_index, ui, &mut interacted);
+
+                ui.add_space(30.0);
+
+                ui_light_selection(screne_renderer, ui, &mut interacted);
             });
         });
 
@@ -885,6 +1527,262 @@ fn create_ui(
     egui_context.end_frame()
 }
 
+fn ui_camera_path(screne_renderer: &mut Renderer, ui: &mut egui::Ui) {
+    ui.label("camera path:");
+
+    let path = &screne_renderer.scene.camera_path;
+    let has_keyframes = !path.keyframes.is_empty();
+
+    ui.label(format!(
+        "{} keyframes, {:.1} s",
+        path.keyframes.len(),
+        path.duration()
+    ));
+
+    if ui
+        .button("add keyframe")
+        .on_hover_text("current view, 2 seconds after the last keyframe")
+        .clicked()
+    {
+        let time = if has_keyframes {
+            path.duration() + 2.0
+        } else {
+            0.0
+        };
+
+        let keyframe = screne_renderer.camera.keyframe(time);
+        screne_renderer.scene.camera_path.add_keyframe(keyframe);
+    }
+
+    if ui
+        .add_enabled(has_keyframes, egui::Button::new("clear keyframes"))
+        .clicked()
+    {
+        screne_renderer.stop_camera_path();
+        screne_renderer.scene.camera_path.keyframes.clear();
+    }
+
+    let path_samples = &mut screne_renderer.path_samples;
+    create_drag_value!(ui, path_samples, 1.0, 1..=100000, "samples: ");
+
+    if let Some((frame, frame_count)) = screne_renderer.path_render_progress() {
+        ui.label(format!("rendering frame {} / {}", frame + 1, frame_count));
+
+        if ui.button("stop render").clicked() {
+            screne_renderer.stop_camera_path();
+        }
+        return;
+    }
+
+    if screne_renderer.is_previewing_path() {
+        if ui.button("stop preview").clicked() {
+            screne_renderer.stop_camera_path();
+        }
+    } else if ui
+        .add_enabled(has_keyframes, egui::Button::new("preview path"))
+        .clicked()
+    {
+        screne_renderer.start_path_preview();
+    }
+
+    if ui
+        .add_enabled(has_keyframes, egui::Button::new("render path"))
+        .on_hover_text("numbered images in the animation folder")
+        .clicked()
+    {
+        screne_renderer.start_path_render();
+    }
+}
+
+fn ui_light_selection(screne_renderer: &mut Renderer, ui: &mut egui::Ui, interacted: &mut bool) {
+    let light_count = screne_renderer.scene.lights.len();
+
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(
+                light_count < screne_renderer.light_capacity,
+                egui::Button::new("add light"),
+            )
+            .on_hover_text("point light at the camera")
+            .clicked()
+        {
+            let light = SceneLight {
+                position: screne_renderer.camera.position.into(),
+                light_type: LIGHT_POINT,
+                direction: [0.0, 1.0, 0.0],
+                intensity: 10.0,
+                color: [1.0, 1.0, 1.0],
+                spot_angle: 30.0,
+                size: [1.0, 1.0],
+                spot_softness: 0.2,
+                _padding: [0; 4],
+            };
+
+            screne_renderer.scene.lights.push(light);
+            screne_renderer.light_index = light_count;
+            *interacted = true;
+        }
+
+        if ui
+            .add_enabled(light_count > 0, egui::Button::new("remove light"))
+            .clicked()
+        {
+            screne_renderer
+                .scene
+                .lights
+                .remove(screne_renderer.light_index);
+            screne_renderer.light_index = screne_renderer.light_index.saturating_sub(1);
+            *interacted = true;
+        }
+    });
+
+    if screne_renderer.scene.lights.is_empty() {
+        return;
+    }
+
+    ui.vertical_centered_justified(|ui: &mut egui::Ui| {
+        ui.label("selected light:");
+        ui.add(
+            egui::Slider::new(
+                &mut screne_renderer.light_index,
+                0..=(screne_renderer.scene.lights.len() - 1),
+            )
+            .integer(),
+        );
+
+        let current_light = &mut screne_renderer.scene.lights[screne_renderer.light_index];
+
+        let light_types = [
+            (LIGHT_POINT, "point"),
+            (LIGHT_SPOT, "spot"),
+            (LIGHT_DIRECTIONAL, "directional"),
+            (LIGHT_RECTANGLE, "rectangle"),
+            (LIGHT_DISK, "disk"),
+        ];
+
+        let selected_name = light_types
+            .iter()
+            .find(|(light_type, _)| *light_type == current_light.light_type)
+            .map_or("unknown", |(_, name)| name);
+
+        egui::ComboBox::from_label("type")
+            .selected_text(selected_name)
+            .show_ui(ui, |ui| {
+                for (light_type, name) in light_types {
+                    if ui
+                        .selectable_value(&mut current_light.light_type, light_type, name)
+                        .changed()
+                    {
+                        *interacted = true;
+                    }
+                }
+            });
+
+        // lights without an area keep a zero size, it would make an area light black
+        let is_area_light = matches!(current_light.light_type, LIGHT_RECTANGLE | LIGHT_DISK);
+
+        if is_area_light && current_light.size.iter().any(|&side| side <= 0.0) {
+            current_light.size = [1.0, 1.0];
+        }
+
+        let light_type = current_light.light_type;
+
+        if light_type != LIGHT_DIRECTIONAL {
+            let position = &mut current_light.position;
+
+            ui.label("location:");
+            ui.horizontal(|ui| {
+                if create_drag_value!(ui, &mut position[0], 0.1, -400.0..=400.0, "X: ") {
+                    *interacted = true;
+                }
+
+                if create_drag_value!(ui, &mut position[1], 0.1, -400.0..=10.0, "Y: ") {
+                    *interacted = true;
+                }
+
+                if create_drag_value!(ui, &mut position[2], 0.1, -400.0..=400.0, "Z: ") {
+                    *interacted = true;
+                }
+            });
+        }
+
+        if light_type != LIGHT_POINT {
+            let direction = &mut current_light.direction;
+            let previous_direction = *direction;
+
+            ui.label("direction:");
+            ui.horizontal(|ui| {
+                if create_drag_value!(ui, &mut direction[0], 0.01, -1.0..=1.0, "X: ") {
+                    *interacted = true;
+                }
+
+                if create_drag_value!(ui, &mut direction[1], 0.01, -1.0..=1.0, "Y: ") {
+                    *interacted = true;
+                }
+
+                if create_drag_value!(ui, &mut direction[2], 0.01, -1.0..=1.0, "Z: ") {
+                    *interacted = true;
+                }
+            });
+
+            // a zero vector points nowhere, the drag that made it is undone
+            if direction.iter().all(|&component| component == 0.0) {
+                *direction = previous_direction;
+            }
+        }
+
+        if ui
+            .color_edit_button_rgb(&mut current_light.color)
+            .on_hover_text("color")
+            .changed()
+        {
+            *interacted = true;
+        }
+
+        let intensity = &mut current_light.intensity;
+
+        if create_drag_value!(ui, intensity, 0.5, 0.0..=1000.0, "intensity: ") {
+            *interacted = true;
+        }
+
+        if light_type == LIGHT_SPOT {
+            let spot_angle = &mut current_light.spot_angle;
+
+            if create_drag_value!(ui, spot_angle, 0.5, 1.0..=89.0, "cone angle: ") {
+                *interacted = true;
+            }
+
+            let spot_softness = &mut current_light.spot_softness;
+
+            if create_drag_value!(ui, spot_softness, 0.01, 0.0..=1.0, "cone softness: ") {
+                *interacted = true;
+            }
+        }
+
+        if light_type == LIGHT_RECTANGLE {
+            let size = &mut current_light.size;
+
+            ui.horizontal(|ui| {
+                if create_drag_value!(ui, &mut size[0], 0.05, 0.01..=100.0, "width: ") {
+                    *interacted = true;
+                }
+
+                if create_drag_value!(ui, &mut size[1], 0.05, 0.01..=100.0, "height: ") {
+                    *interacted = true;
+                }
+            });
+        }
+
+        if light_type == LIGHT_DISK {
+            let radius = &mut current_light.size[0];
+
+            if create_drag_value!(ui, radius, 0.05, 0.01..=100.0, "radius: ") {
+                *interacted = true;
+            }
+        }
+    });
+}
+
 fn ui_material_selection(
     screne_renderer: &mut Renderer,
     material_index: usize,
@@ -945,5 +1843,67 @@ fn ui_material_selection(
         if create_drag_value!(ui, refraction_index, 0.01, 0.0..=5.0, "refraction index: ") {
             *interacted = true;
         }
+
+        let texture_count = screne_renderer.scene.image_textures.len() as u32;
+
+        ui.label("texture maps:");
+
+        let texture_maps = [
+            (&mut current_material.normal_map_index, "normal map"),
+            (&mut current_material.height_map_index, "height map"),
+            (
+                &mut current_material.roughness_map_index,
+                "roughness map (G)",
+            ),
+            (&mut current_material.specular_map_index, "specular map (B)"),
+            (&mut current_material.emission_map_index, "emission map"),
+        ];
+
+        for (map_index, name) in texture_maps {
+            if ui_texture_map_selection(ui, map_index, name, texture_count) {
+                *interacted = true;
+            }
+        }
+
+        let has_normal_maps = current_material.normal_map_index != NO_TEXTURE
+            || current_material.height_map_index != NO_TEXTURE;
+
+        if has_normal_maps {
+            let normal_strength = &mut current_material.normal_strength;
+
+            if create_drag_value!(ui, normal_strength, 0.01, 0.0..=10.0, "normal strength: ") {
+                *interacted = true;
+            }
+        }
     });
 }
+
+fn ui_texture_map_selection(
+    ui: &mut egui::Ui,
+    map_index: &mut u32,
+    name: &str,
+    texture_count: u32,
+) -> bool {
+    // any texture of the scene can be used as a map, the name shows its index in the texture list
+    let texture_name = |index: u32| match index {
+        NO_TEXTURE => "none".to_string(),
+        index => format!("texture {index}"),
+    };
+
+    let mut changed = false;
+
+    egui::ComboBox::from_label(name)
+        .selected_text(texture_name(*map_index))
+        .show_ui(ui, |ui| {
+            for index in std::iter::once(NO_TEXTURE).chain(0..texture_count) {
+                if ui
+                    .selectable_value(map_index, index, texture_name(index))
+                    .changed()
+                {
+                    changed = true;
+                }
+            }
+        });
+
+    changed
+}
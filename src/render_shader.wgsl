@@ -39,5 +39,14 @@ fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
 
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(tex, samp, input.tex_coords);
+    // the rendered image can be smaller or larger than the window, smaller images are upscaled by the linear sampler,
+    // larger images are averaged over the whole window pixel with four samples so they do not alias
+    let footprint: vec2<f32> = fwidth(input.tex_coords) * 0.25;
+
+    let color: vec4<f32> = textureSample(tex, samp, input.tex_coords + vec2<f32>(-footprint.x, -footprint.y))
+        + textureSample(tex, samp, input.tex_coords + vec2<f32>(footprint.x, -footprint.y))
+        + textureSample(tex, samp, input.tex_coords + vec2<f32>(-footprint.x, footprint.y))
+        + textureSample(tex, samp, input.tex_coords + vec2<f32>(footprint.x, footprint.y));
+
+    return color * 0.25;
 }
\ No newline at end of file
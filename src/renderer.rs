@@ -1,18 +1,48 @@
 use crate::buffers::{
-    ObjectInfo, Params, RayCamera, SceneMaterial, SceneSphere, SceneTriangle, SubObjectInfo,
+    ObjectInfo, PRIMITIVE_SPHERE, PRIMITIVE_TRIANGLE, Params, RayCamera, SceneEmitter, SceneLight,
+    SceneMaterial, SceneSphere, SceneTriangle, SubObjectInfo, TONE_MAP_ACES,
 };
 
 use crate::triangle_object::SceneObject;
 
 use crate::image_texture::ImageTexture;
 
-use super::camera::Camera;
+use super::camera::{Camera, CameraBookmark, CameraSettings};
+
+use super::camera_path::CameraPath;
 
 use super::buffers;
 
+use super::denoiser::Denoiser;
+
+use super::display::DisplayPass;
+
 use egui::Context;
 
-use wgpu::{BindGroup, BindGroupLayout, CommandEncoder, Device, Queue, Texture};
+use glam::{Vec3A, vec2};
+
+use wgpu::{BindGroup, BindGroupLayout, Device, Queue, Texture};
+
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+use std::time::Instant;
+
+// folder for the numbered images of a rendered camera path
+const ANIMATION_DIRECTORY: &str = "./animation";
+
+// folder for the screenshots of the rendered image
+const SCREENSHOT_DIRECTORY: &str = "./screenshots";
+
+// the automatic render scale waits this long after the camera stops before it goes back to full resolution
+const AUTO_SCALE_DELAY: f32 = 0.3;
+// fraction of the render scale that is used while the camera moves
+const AUTO_SCALE_FACTOR: f32 = 0.5;
+
+// radians the orbit camera turns for every pixel the mouse is dragged
+const ORBIT_DRAG_SPEED: f32 = 0.005;
+
+// lights the UI can have at most, unless the scene starts with more
+const LIGHT_SLOTS: usize = 8;
 
 #[derive(Debug, Clone)]
 pub struct RenderScene {
@@ -21,6 +51,10 @@ pub struct RenderScene {
     pub image_textures: Vec<ImageTexture>,
     pub materials: Vec<SceneMaterial>,
     pub objects: Vec<SceneObject>,
+    pub lights: Vec<SceneLight>,
+    pub camera: CameraSettings,
+    pub camera_bookmarks: Vec<CameraBookmark>,
+    pub camera_path: CameraPath,
     pub environment_map: ImageTexture,
     pub env_map_size: [u32; 2],
 }
@@ -33,9 +67,42 @@ pub struct Renderer<'a> {
     pub accumulate: bool,
     pub object_index: usize,
     pub sphere_index: usize,
+    pub light_index: usize,
+    pub light_capacity: usize, // size of the light array in the compute shader
     pub compute_per_frame: u32,
-    accumulation_index: u32,
+    pub max_diffuse_bounces: u32,
+    pub max_specular_bounces: u32,
+    pub max_transmission_bounces: u32,
+    pub roulette_depth: u32,
+    pub filter_type: u32,
+    pub filter_radius: f32,
+    pub motion_blur: bool,
+    pub exposure: f32, // stops, every step doubles the brightness
+    pub tone_mapper: u32,
+    pub denoise: bool,
+    pub render_scale: f32, // size of the rendered image compared to the window
+    pub auto_render_scale: bool,
+    pub sample_target: Option<u32>, // accumulation stops once every pixel has this many samples
+    pub output_path: Option<String>, // image saved when the sample target is reached
+    pub path_samples: u32,
+    pub orbit_mode: bool,
+    pub turntable: bool,
+    pub turntable_speed: f32, // degrees per second
+    seed: u32,
+    window_size: winit::dpi::PhysicalSize<u32>,
+    tile_origin: PhysicalPosition<u32>, // part of the image the pixel buffers hold
+    tile_size: PhysicalSize<u32>,
+    current_render_scale: f32,
+    last_camera_movement: Instant,
+    turntable_time: Instant,
+    path_preview_start: Option<Instant>,
+    path_render_frame: Option<u32>,
+    accumulated_samples: u32,
+    emitter_count: u32,
     buffers: buffers::DataBuffers,
+    denoiser: Denoiser,
+    denoised: bool, // the denoiser already filtered the current radiance
+    display: DisplayPass,
 }
 
 impl Renderer<'_> {
@@ -47,29 +114,40 @@ impl Renderer<'_> {
         size: winit::dpi::PhysicalSize<u32>,
         params: Params,
     ) -> (Renderer<'a>, BindGroupLayout, BindGroup) {
-        let camera_rays = camera.recalculate_ray_directions();
         let accumulate = params.accumulate == 1;
 
         let (object_info_vec, sub_object_info_vec, triangles) = get_triangle_data(&scene);
 
-        let ray_camera: RayCamera = RayCamera {
-            origin: camera.position.into(),
-            _padding: [0; 4],
-        };
+        let emitters = get_emitter_data(&scene);
+        let emitter_capacity = scene.spheres.len() + triangles.len();
+        let light_capacity = light_capacity(&scene.lights);
+
+        let ray_camera: RayCamera = camera.get_ray_camera();
+
+        // the pixel buffers only hold the tile of the image that is rendered
+        let tile_origin = PhysicalPosition::new(params.tile_x, params.tile_y);
+        let tile_size = PhysicalSize::new(params.tile_width, params.tile_height);
 
         let (buffers, bind_group_layout, compute_bind_group) = buffers::DataBuffers::new(
             device,
-            &size,
+            &tile_size,
             ray_camera,
-            &camera_rays,
             &scene.materials,
             &scene.spheres,
             &triangles,
             &object_info_vec,
             &sub_object_info_vec,
+            emitter_capacity,
+            light_capacity,
             &[params],
         );
 
+        buffers.update_emitters(queue, &emitters);
+        buffers.update_lights(queue, &scene.lights);
+
+        let denoiser = Denoiser::new(device, &buffers);
+        let display = DisplayPass::new(device, &buffers, &denoiser);
+
         buffers.update_texture_buffer(
             &scene.image_textures,
             queue,
@@ -92,70 +170,518 @@ impl Renderer<'_> {
             accumulate,
             object_index: 0,
             sphere_index: 0,
+            light_index: 0,
+            light_capacity,
             compute_per_frame: params.compute_per_frame,
-            accumulation_index: 1,
+            max_diffuse_bounces: params.max_diffuse_bounces,
+            max_specular_bounces: params.max_specular_bounces,
+            max_transmission_bounces: params.max_transmission_bounces,
+            roulette_depth: params.roulette_depth,
+            filter_type: params.filter_type,
+            filter_radius: params.filter_radius,
+            motion_blur: params.motion_blur == 1,
+            exposure: 0.0,
+            tone_mapper: TONE_MAP_ACES,
+            denoise: false,
+            render_scale: 1.0,
+            auto_render_scale: false,
+            sample_target: None,
+            output_path: None,
+            seed: params.seed,
+            window_size: size,
+            tile_origin,
+            tile_size,
+            current_render_scale: 1.0,
+            last_camera_movement: Instant::now(),
+            path_samples: 256,
+            orbit_mode: false,
+            turntable: false,
+            turntable_speed: 20.0,
+            turntable_time: Instant::now(),
+            path_preview_start: None,
+            path_render_frame: None,
+            accumulated_samples: 0,
+            emitter_count: emitters.len() as u32,
             buffers,
+            denoiser,
+            denoised: false,
+            display,
         };
 
         (renderer, bind_group_layout, compute_bind_group)
     }
 
-    pub fn on_resize(&mut self, size: &winit::dpi::PhysicalSize<u32>) {
+    pub fn on_resize(
+        &mut self,
+        size: &winit::dpi::PhysicalSize<u32>,
+        compute_bindgroup_layout: &BindGroupLayout,
+    ) -> BindGroup {
+        // returns the compute bind group for the new pixel buffers
+        self.window_size = *size;
+        self.resize_pixels(compute_bindgroup_layout)
+    }
+
+    pub fn render_size(&self) -> winit::dpi::PhysicalSize<u32> {
+        // resolution of the rendered image, the render shader scales it to the window,
+        // large windows and render scales are lowered to what the pixel buffers of the device can hold
+        let scale =
+            |length: u32| ((length as f32 * self.current_render_scale).round() as u32).max(1);
+
+        let size = winit::dpi::PhysicalSize::new(
+            scale(self.window_size.width),
+            scale(self.window_size.height),
+        );
+
+        buffers::fit_pixel_buffer_limits(size, &self.device.limits())
+    }
+
+    pub fn update_render_scale(
+        &mut self,
+        compute_bindgroup_layout: &BindGroupLayout,
+    ) -> Option<BindGroup> {
+        // returns a new compute bind group when the resolution changed
+        let camera_moving = self.last_camera_movement.elapsed().as_secs_f32() < AUTO_SCALE_DELAY;
+
+        let target_scale = if self.auto_render_scale && camera_moving {
+            (self.render_scale * AUTO_SCALE_FACTOR).max(0.25)
+        } else {
+            self.render_scale
+        };
+
+        if target_scale == self.current_render_scale {
+            return None;
+        }
+
+        self.current_render_scale = target_scale;
+        Some(self.resize_pixels(compute_bindgroup_layout))
+    }
+
+    fn resize_pixels(&mut self, compute_bindgroup_layout: &BindGroupLayout) -> BindGroup {
+        let size = self.render_size();
+
         self.camera.on_resize(size.width, size.height);
 
-        self.reset_accumulation()
+        self.tile_origin = PhysicalPosition::new(0, 0);
+        self.tile_size = size;
+
+        let compute_bind_group = self.buffers.resize(
+            self.device,
+            size.width,
+            size.height,
+            compute_bindgroup_layout,
+        );
+        self.denoiser.resize(self.device, &self.buffers);
+        self.display
+            .resize(self.device, &self.buffers, &self.denoiser);
+
+        self.update_camera();
+        self.reset_accumulation();
+
+        compute_bind_group
+    }
+
+    pub fn set_tile(
+        &mut self,
+        origin: PhysicalPosition<u32>,
+        size: PhysicalSize<u32>,
+        compute_bindgroup_layout: &BindGroupLayout,
+    ) -> Option<BindGroup> {
+        // renders only a part of the image, the camera keeps the size of the whole image,
+        // returns a new compute bind group when the pixel buffers changed size
+        self.tile_origin = origin;
+
+        let compute_bind_group = (size != self.tile_size).then(|| {
+            self.tile_size = size;
+            let compute_bind_group = self.buffers.resize(
+                self.device,
+                size.width,
+                size.height,
+                compute_bindgroup_layout,
+            );
+            self.denoiser.resize(self.device, &self.buffers);
+            self.display
+                .resize(self.device, &self.buffers, &self.denoiser);
+            compute_bind_group
+        });
+
+        self.reset_accumulation();
+
+        compute_bind_group
     }
 
     pub fn on_update(&mut self, mouse_delta: egui::Vec2, egui_context: &Context) {
         let moved = self.camera.on_update(mouse_delta, egui_context);
 
         if moved {
+            // flying by hand takes over from the path preview
+            self.path_preview_start = None;
+            self.last_camera_movement = Instant::now();
+
             self.reset_accumulation();
-            let new_rays = self.camera.recalculate_ray_directions();
+            self.update_camera();
+        };
+    }
 
-            let new_camera = RayCamera {
-                origin: self.camera.position.into(),
-                _padding: [0; 4],
-            };
+    pub fn start_orbit(&mut self) {
+        // points the camera at the selected object, which becomes the orbit center
+        let Some(object) = self.scene.objects.get(self.object_index) else {
+            return;
+        };
 
-            self.queue.write_buffer(
-                &self.buffers.camera_buffer,
-                0,
-                bytemuck::cast_slice(&[new_camera]),
-            );
+        self.orbit_mode = true;
+        self.camera.look_at(object.center_location);
+
+        self.reset_accumulation();
+        self.update_camera();
+    }
+
+    pub fn on_orbit(&mut self, drag_delta: egui::Vec2, pan_delta: egui::Vec2, scroll: f32) {
+        if drag_delta == egui::Vec2::ZERO && pan_delta == egui::Vec2::ZERO && scroll == 0.0 {
+            return;
+        }
+
+        self.camera.orbit(
+            drag_delta.x * ORBIT_DRAG_SPEED,
+            drag_delta.y * ORBIT_DRAG_SPEED,
+        );
+        self.camera.pan(pan_delta);
+
+        if scroll != 0.0 {
+            self.camera.zoom(scroll);
+        }
+
+        self.path_preview_start = None;
+        self.last_camera_movement = Instant::now();
+
+        self.reset_accumulation();
+        self.update_camera();
+    }
+
+    pub fn update_turntable(&mut self) {
+        // spins the orbit camera around the center at a constant speed
+        let elapsed = self.turntable_time.elapsed().as_secs_f32();
+        self.turntable_time = Instant::now();
+
+        if !self.orbit_mode || !self.turntable || self.path_render_frame.is_some() {
+            return;
+        }
+
+        self.camera
+            .orbit(self.turntable_speed.to_radians() * elapsed, 0.0);
+        self.last_camera_movement = Instant::now();
+
+        self.reset_accumulation();
+        self.update_camera();
+    }
+
+    pub fn go_to_bookmark(&mut self, index: usize) {
+        if let Some(bookmark) = self.scene.camera_bookmarks.get(index) {
+            self.camera.start_transition(bookmark);
+        }
+    }
+
+    pub fn update_camera_transition(&mut self) {
+        if self.camera.update_transition() {
+            self.last_camera_movement = Instant::now();
+            self.reset_accumulation();
+            self.update_camera();
+        }
+    }
+
+    pub fn start_path_preview(&mut self) {
+        self.path_render_frame = None;
+        self.path_preview_start = Some(Instant::now());
+    }
+
+    pub fn start_path_render(&mut self) {
+        if let Err(error) = std::fs::create_dir_all(ANIMATION_DIRECTORY) {
+            println!("could not create the animation folder, {}", error);
+            return;
+        }
+
+        self.path_preview_start = None;
+        self.path_render_frame = Some(0);
+        self.accumulate = true;
+
+        self.camera.follow_path(&self.scene.camera_path, 0.0);
+        self.update_camera();
+        self.reset_accumulation();
+    }
+
+    pub fn stop_camera_path(&mut self) {
+        self.path_preview_start = None;
+        self.path_render_frame = None;
+    }
+
+    pub fn path_render_progress(&self) -> Option<(u32, u32)> {
+        self.path_render_frame
+            .map(|frame| (frame, self.scene.camera_path.frame_count()))
+    }
+
+    pub fn is_previewing_path(&self) -> bool {
+        self.path_preview_start.is_some()
+    }
+
+    pub fn update_camera_path(&mut self) {
+        // plays the camera path in real time
+        if let Some(start) = self.path_preview_start {
+            let time = start.elapsed().as_secs_f32();
+
+            if time > self.scene.camera_path.duration() {
+                self.path_preview_start = None;
+            }
+
+            if self.camera.follow_path(&self.scene.camera_path, time) {
+                self.last_camera_movement = Instant::now();
+                self.reset_accumulation();
+                self.update_camera();
+            }
+        }
+
+        // renders the camera path frame by frame, every frame gets the same amount of samples
+        if let Some(frame) = self.path_render_frame {
+            if self.sample_count() < self.path_samples {
+                return;
+            }
+
+            let path = format!("{}/frame_{:04}.png", ANIMATION_DIRECTORY, frame + 1);
+            self.save_image(&path);
+
+            let next_frame = frame + 1;
+
+            if next_frame >= self.scene.camera_path.frame_count() {
+                println!("camera path rendered to {}", ANIMATION_DIRECTORY);
+                self.path_render_frame = None;
+                return;
+            }
+
+            let time = next_frame as f32 / self.scene.camera_path.frames_per_second;
+
+            self.path_render_frame = Some(next_frame);
+            self.camera.follow_path(&self.scene.camera_path, time);
+            self.update_camera();
+            self.reset_accumulation();
+        }
+    }
+
+    pub fn save_screenshot(&self) {
+        // the output texture only holds the rendered scene, so the UI is never part of the screenshot
+        if let Err(error) = std::fs::create_dir_all(SCREENSHOT_DIRECTORY) {
+            println!("could not create the screenshot folder, {}", error);
+            return;
+        }
+
+        let path = format!("{}/screenshot_{}.png", SCREENSHOT_DIRECTORY, timestamp());
+        self.save_image(&path);
+
+        println!("screenshot saved to {}", path);
+    }
 
-            self.buffers.update_ray_directions(self.queue, &new_rays);
+    pub fn export_hdr_image(&self) {
+        // linear radiance without exposure or tone mapping, for compositing in other tools
+        if !self.accumulate || self.sample_count() == 0 {
+            println!("the HDR export needs light accumulation to be turned on");
+            return;
+        }
+
+        if let Err(error) = std::fs::create_dir_all(SCREENSHOT_DIRECTORY) {
+            println!("could not create the screenshot folder, {}", error);
+            return;
+        }
+
+        let path = format!("{}/render_{}", SCREENSHOT_DIRECTORY, timestamp());
+
+        self.save_image(&format!("{}.exr", path));
+        self.save_image(&format!("{}.pfm", path));
+
+        println!(
+            "HDR image with {} samples saved to {}.exr and .pfm",
+            self.sample_count(),
+            path
+        );
+    }
+
+    pub fn read_pixels(&self, hdr: bool) -> Vec<[f32; 4]> {
+        // hdr pixels are the average of the accumulated samples without exposure or tone mapping,
+        // the others the linear colors of the displayed image, both only cover the current tile
+
+        // the denoiser or the display settings might have changed since the last frame
+        self.refresh_display();
+
+        if hdr && self.denoise {
+            return self
+                .denoiser
+                .read_denoised(self.device, self.queue)
+                .into_iter()
+                .map(|[r, g, b, _]| [r, g, b, 1.0])
+                .collect();
+        }
+
+        if !hdr {
+            return self.buffers.read_output_texture(self.device, self.queue);
+        }
+
+        let samples = self.sample_count().max(1) as f32;
+
+        self.buffers
+            .read_accumulation_buffer(self.device, self.queue)
+            .iter()
+            .map(|sum| [sum[0] / samples, sum[1] / samples, sum[2] / samples, 1.0])
+            .collect()
+    }
+
+    pub fn save_image(&self, path: &str) {
+        // .exr and .pfm get the linear radiance, other formats the displayed image
+        let pixels = self.read_pixels(is_hdr_path(path));
+        save_pixels(path, self.tile_size.width, self.tile_size.height, &pixels);
+    }
+
+    pub fn reached_sample_target(&self) -> bool {
+        // a rendering camera path has its own sample count per frame
+        self.accumulate
+            && self.path_render_frame.is_none()
+            && self
+                .sample_target
+                .is_some_and(|target| self.sample_count() >= target)
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        // samples per pixel in the accumulation buffer
+        self.accumulated_samples
+    }
+
+    fn frame_samples(&self) -> u32 {
+        // the last dispatch before a sample target only computes the samples that are still missing,
+        // a rendering camera path has its own sample count per frame
+        let target = match self.path_render_frame {
+            Some(_) => Some(self.path_samples),
+            None => self.sample_target,
         };
+
+        match target {
+            Some(target) if self.accumulate && target > self.accumulated_samples => self
+                .compute_per_frame
+                .min(target - self.accumulated_samples),
+            _ => self.compute_per_frame,
+        }
+    }
+
+    pub fn focus_on_pixel(&mut self, x: f32, y: f32) -> bool {
+        // moves the plane of focus to the closest surface under the window pixel
+        let normalized_coord = vec2(
+            x / self.window_size.width as f32,
+            y / self.window_size.height as f32,
+        ) * 2.0
+            - 1.0;
+
+        let Some((origin, direction)) = self.camera.primary_ray(normalized_coord) else {
+            return false;
+        };
+
+        let sphere_hits = self
+            .scene
+            .spheres
+            .iter()
+            .filter_map(|sphere| sphere.intersect(origin, direction));
+
+        let triangle_hits = self
+            .scene
+            .objects
+            .iter()
+            .filter(|object| {
+                ray_in_bounds(
+                    origin,
+                    direction,
+                    object.object_info.min_bounds.into(),
+                    object.object_info.max_bounds.into(),
+                )
+            })
+            .flat_map(|object| &object.object_triangles)
+            .filter_map(|triangle| triangle.intersect(origin, direction));
+
+        let closest_hit = sphere_hits.chain(triangle_hits).reduce(f32::min);
+
+        match closest_hit {
+            Some(distance) => {
+                // the focus distance is measured along the view direction
+                self.camera.focus_distance = distance * direction.dot(self.camera.direction);
+
+                self.update_camera();
+                self.reset_accumulation();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn update_camera(&self) {
+        // primary rays are generated in the compute shader from the inverse camera matrices
+        let new_camera: RayCamera = self.camera.get_ray_camera();
+
+        self.queue.write_buffer(
+            &self.buffers.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[new_camera]),
+        );
     }
 
     pub fn reset_accumulation(&mut self) {
-        self.accumulation_index = 1;
+        self.accumulated_samples = 0;
+
+        let params = self.get_params();
+
+        self.buffers
+            .reset_accumulation(self.device, self.queue, &[params]);
+    }
 
-        let params = Params {
+    fn get_params(&self) -> Params {
+        Params {
             screen_width: self.camera.viewport_width,
-            accumulation_index: self.accumulation_index,
+            accumulated_samples: self.accumulated_samples,
             accumulate: self.accumulate as u32,
             sphere_count: self.scene.spheres.len() as u32,
             object_count: self.scene.objects.len() as u32,
-            compute_per_frame: self.compute_per_frame,
+            compute_per_frame: self.frame_samples(),
             texture_width: self.scene.texture_size[0],
             texture_height: self.scene.texture_size[1],
             textue_count: self.scene.image_textures.len() as u32,
             env_map_width: self.scene.env_map_size[0],
             env_map_height: self.scene.env_map_size[1],
-            _padding: [0; 4],
-        };
-
-        self.buffers
-            .reset_accumulation(self.device, self.queue, &[params]);
+            emitter_count: self.emitter_count,
+            light_count: self.scene.lights.len() as u32,
+            max_diffuse_bounces: self.max_diffuse_bounces,
+            max_specular_bounces: self.max_specular_bounces,
+            max_transmission_bounces: self.max_transmission_bounces,
+            roulette_depth: self.roulette_depth,
+            screen_height: self.camera.viewport_height,
+            filter_type: self.filter_type,
+            filter_radius: self.filter_radius,
+            motion_blur: self.motion_blur as u32,
+            seed: self.seed,
+            tile_x: self.tile_origin.x,
+            tile_y: self.tile_origin.y,
+            tile_width: self.tile_size.width,
+            tile_height: self.tile_size.height,
+        }
     }
 
     pub fn update_scene(&mut self) {
+        // materials might have started or stopped emitting light
+        let new_emitters = get_emitter_data(&self.scene);
+        self.buffers.update_emitters(self.queue, &new_emitters);
+        self.emitter_count = new_emitters.len() as u32;
+
         self.reset_accumulation();
 
+        self.camera.recalculate_projection();
+        self.update_camera();
+
         let new_spheres = &self.scene.spheres;
         self.buffers.update_spheres(self.queue, new_spheres);
 
+        let new_lights = &self.scene.lights;
+        self.buffers.update_lights(self.queue, new_lights);
+
         for object in &mut self.scene.objects {
             object.update_triangles();
         }
@@ -209,29 +735,50 @@ impl Renderer<'_> {
                     label: Some("Encoder"),
                 });
 
-        // ###################################### update accumulation ########################################
-        let width = self.camera.viewport_width;
-        let height = self.camera.viewport_height;
+        if self.reached_sample_target() {
+            // the image is done, it is saved once and the path tracer gets a rest
+            if let Some(path) = self.output_path.take() {
+                self.save_image(&path);
+                println!("{} samples saved to {}", self.sample_count(), path);
+            }
+        } else {
+            self.trace_frame(&mut compute_encoder, compute_pipeline, compute_bind_group);
+            self.denoised = false;
+        }
 
-        if self.accumulate {
-            let params = Params {
-                screen_width: width,
-                accumulation_index: self.accumulation_index,
-                accumulate: self.accumulate as u32,
-                sphere_count: self.scene.spheres.len() as u32,
-                object_count: self.scene.objects.len() as u32,
-                compute_per_frame: self.compute_per_frame,
-                texture_width: self.scene.texture_size[0],
-                texture_height: self.scene.texture_size[1],
-                textue_count: self.scene.image_textures.len() as u32,
-                env_map_width: self.scene.env_map_size[0],
-                env_map_height: self.scene.env_map_size[1],
-                _padding: [0; 4],
-            };
+        // the display settings can still change once the image is done, so it is tone mapped every frame
+        if self.denoise && !self.denoised {
+            self.denoiser.denoise(&mut compute_encoder);
+            self.denoised = true;
+        }
+
+        self.display.display(
+            self.queue,
+            &mut compute_encoder,
+            self.exposure,
+            self.tone_mapper,
+            self.denoise,
+        );
+
+        self.queue.submit(Some(compute_encoder.finish()));
+    }
 
-            self.buffers.update_accumulation(self.queue, &[params]);
+    fn trace_frame(
+        &mut self,
+        compute_encoder: &mut wgpu::CommandEncoder,
+        compute_pipeline: &wgpu::ComputePipeline,
+        compute_bind_group: &BindGroup,
+    ) {
+        // ###################################### update accumulation ########################################
+        let width = self.tile_size.width;
+        let height = self.tile_size.height;
+
+        // settings like the bounce limits can change without resetting the accumulation
+        let params = self.get_params();
+        self.buffers.update_accumulation(self.queue, &[params]);
 
-            self.accumulation_index += 1;
+        if self.accumulate {
+            self.accumulated_samples += params.compute_per_frame;
         }
         // ###################################### compute step ########################################
 
@@ -245,54 +792,132 @@ impl Renderer<'_> {
             compute_pass.set_bind_group(0, compute_bind_group, &[]);
             compute_pass.dispatch_workgroups(width.div_ceil(8), height.div_ceil(8), 1);
         }
+    }
 
-        self.queue.submit(Some(compute_encoder.finish()));
+    fn refresh_display(&self) {
+        // filters and tone maps the pixels of the last compute frame again outside of a frame
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Display Encoder"),
+            });
 
-        // ###################################### copying buffers to texture ########################################
-    }
+        if self.denoise {
+            self.denoiser.denoise(&mut encoder);
+        }
 
-    pub fn update_texture(&mut self, encoder: &mut CommandEncoder, texture: &Texture) {
-        // ###################################### update accumulation ########################################
-        let width = self.camera.viewport_width;
-        let height = self.camera.viewport_height;
-
-        let bytes_per_row = self.calculate_bytes_per_row(width);
-
-        // Copy the GPU output buffer to the texture to be displayed
-        encoder.copy_buffer_to_texture(
-            wgpu::ImageCopyBuffer {
-                buffer: &self.buffers.output_buffer,
-                layout: wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: Some(bytes_per_row), // 4 bytes per pixel
-                    rows_per_image: Some(height),
-                },
-            },
-            wgpu::ImageCopyTexture {
-                texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
+        self.display.display(
+            self.queue,
+            &mut encoder,
+            self.exposure,
+            self.tone_mapper,
+            self.denoise,
         );
+
+        self.queue.submit(Some(encoder.finish()));
     }
 
-    pub fn calculate_bytes_per_row(&self, width: u32) -> u32 {
-        // the gpu buffer has to be 256 * n bytes per row
+    pub fn output_texture(&self) -> &Texture {
+        // the tone mapped image the render pipeline samples to draw the scene
+        &self.buffers.output_texture
+    }
+}
+
+pub fn is_hdr_path(path: &str) -> bool {
+    path.ends_with(".exr") || path.ends_with(".pfm")
+}
+
+pub fn save_pixels(path: &str, width: u32, height: u32, pixels: &[[f32; 4]]) {
+    // .exr and .pfm keep the linear values, other formats are sRGB encoded
+    // and the alpha channel holds no coverage, every pixel is opaque
+    let result = if path.ends_with(".pfm") {
+        save_pfm(path, width, height, pixels)
+    } else if path.ends_with(".exr") {
+        let image = image::Rgba32FImage::from_raw(width, height, pixels.concat())
+            .expect("pixels do not match the image size");
+
+        image.save(path).map_err(std::io::Error::other)
+    } else {
+        let encoded = pixels
+            .iter()
+            .flat_map(|&[r, g, b, _]| {
+                [linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), 255]
+            })
+            .collect();
+
+        let image = image::RgbaImage::from_raw(width, height, encoded)
+            .expect("pixels do not match the image size");
+
+        image.save(path).map_err(std::io::Error::other)
+    };
+
+    if let Err(error) = result {
+        println!("could not save image to {}, {}", path, error);
+    }
+}
 
-        let bytes_per_pixel = 4; // RGBA8Unorm = 4 bytes per pixel
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
 
-        let value = width * bytes_per_pixel;
-        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let encoded = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
 
-        // bytes per row
-        (value + alignment - 1) & !(alignment - 1)
+    (encoded * 255.0).round() as u8
+}
+
+fn save_pfm(path: &str, width: u32, height: u32, pixels: &[[f32; 4]]) -> std::io::Result<()> {
+    // portable float map, rgb f32 rows from the bottom to the top, a negative scale means little endian
+    let mut data = format!("PF\n{} {}\n-1.0\n", width, height).into_bytes();
+
+    for row in pixels.chunks_exact(width as usize).rev() {
+        for pixel in row {
+            for channel in &pixel[..3] {
+                data.extend_from_slice(&channel.to_le_bytes());
+            }
+        }
     }
+
+    std::fs::write(path, data)
+}
+
+fn timestamp() -> String {
+    // current UTC date and time as year-month-day_hour-minute-second, usable in file names
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    let days = (seconds / 86400) as i64;
+    let time_of_day = seconds % 86400;
+
+    // civil date from the days since 1970-01-01, years start in march so the leap day is last
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
 }
 
 pub fn get_triangle_data(
@@ -318,3 +943,59 @@ pub fn get_triangle_data(
         .collect();
     (object_info_vec, sub_object_info_vec, triangles)
 }
+
+fn ray_in_bounds(origin: Vec3A, direction: Vec3A, min_bounds: Vec3A, max_bounds: Vec3A) -> bool {
+    // slab test, same as in the compute shader
+    let inv_direction = direction.recip();
+    let min_t = (min_bounds - origin) * inv_direction;
+    let max_t = (max_bounds - origin) * inv_direction;
+
+    let near_t = min_t.min(max_t).max_element();
+    let far_t = min_t.max(max_t).min_element();
+
+    near_t <= far_t && far_t >= 0.0
+}
+
+pub fn light_capacity(lights: &[SceneLight]) -> usize {
+    // the light array of the compute shader leaves room for lights added in the UI
+    lights.len().max(LIGHT_SLOTS)
+}
+
+pub fn get_emitter_data(scene: &RenderScene) -> Vec<SceneEmitter> {
+    // list of all spheres and triangles with an emissive material, used for sampling the lights directly
+    let is_emissive =
+        |material_index: u32| scene.materials[material_index as usize].emission_power > 0.0;
+
+    let sphere_emitters = scene
+        .spheres
+        .iter()
+        .enumerate()
+        .filter(|(_, sphere)| is_emissive(sphere.material_index))
+        .map(|(i, _)| SceneEmitter {
+            primitive_type: PRIMITIVE_SPHERE,
+            primitive_index: i as u32,
+            object_index: 0,
+            _padding: [0; 4],
+        });
+
+    // triangles are indexed in the same order as they are in the triangle buffer
+    let mut triangle_index = 0;
+    let mut triangle_emitters = vec![];
+
+    for (object_index, object) in scene.objects.iter().enumerate() {
+        let triangle_count = object.object_triangles.len() as u32;
+
+        if is_emissive(object.material_index) {
+            triangle_emitters.extend((0..triangle_count).map(|i| SceneEmitter {
+                primitive_type: PRIMITIVE_TRIANGLE,
+                primitive_index: triangle_index + i,
+                object_index: object_index as u32,
+                _padding: [0; 4],
+            }));
+        }
+
+        triangle_index += triangle_count;
+    }
+
+    sphere_emitters.chain(triangle_emitters).collect()
+}
@@ -43,6 +43,9 @@ pub struct SceneObject {
     pub rotation: Vec3A,
     pub scale: f32,
     pub transformation: Vec3A,
+    // change of the transformation and rotation while the shutter is open
+    pub motion_transformation: Vec3A,
+    pub motion_rotation: Vec3A,
     pub center_location: Vec3A,
     pub material_index: u32,
     pub object_info: ObjectInfo,
@@ -106,7 +109,20 @@ impl SceneObject {
             max_bounds: max_coords.into(),
             sub_object_count: 0, // temp values
             material_index,
-            _padding: [0; 12],
+            moving: 0,
+            _padding: [0; 8],
+            swept_min_bounds: min_coords.into(),
+            _padding2: [0; 4],
+            swept_max_bounds: max_coords.into(),
+            _padding3: [0; 4],
+            translation: total_transformation.into(),
+            _padding4: [0; 4],
+            rotation: [0.0; 3],
+            _padding5: [0; 4],
+            motion_translation: [0.0; 3],
+            _padding6: [0; 4],
+            motion_rotation: [0.0; 3],
+            _padding7: [0; 4],
         };
 
         let center_location = (min_coords + max_coords) / 2.0;
@@ -117,6 +133,8 @@ impl SceneObject {
             scale: 1.0,
             rotation: Vec3A::ZERO,
             transformation: total_transformation,
+            motion_transformation: Vec3A::ZERO,
+            motion_rotation: Vec3A::ZERO,
             center_location,
             material_index,
             object_info,
@@ -140,12 +158,54 @@ impl SceneObject {
         self.object_info.min_bounds = min_coords.into();
         self.object_info.max_bounds = max_coords.into();
 
+        self.update_motion(&transformed_points, min_coords, max_coords);
+
         let triangles: Vec<SceneTriangle> =
             generate_triangles(&self.point_indexes, &transformed_points);
 
         self.object_triangles = triangles;
     }
 
+    fn update_motion(
+        &mut self,
+        transformed_points: &[Vec3A],
+        min_coords: Vec3A,
+        max_coords: Vec3A,
+    ) {
+        // the triangles stay at the start of the shutter, the shader moves the rays into that pose instead
+        let info = &mut self.object_info;
+
+        info.translation = self.transformation.into();
+        info.rotation = self.rotation.into();
+        info.motion_translation = self.motion_transformation.into();
+        info.motion_rotation = self.motion_rotation.into();
+
+        let end_transformation = self.transformation + self.motion_transformation;
+
+        let (swept_min, swept_max) = if self.motion_rotation != Vec3A::ZERO {
+            // every point stays within the same distance of the rotation center along its path
+            let radius = transformed_points
+                .iter()
+                .map(|point| point.distance(self.transformation))
+                .fold(0.0, f32::max);
+
+            (
+                self.transformation.min(end_transformation) - radius,
+                self.transformation.max(end_transformation) + radius,
+            )
+        } else {
+            (
+                min_coords.min(min_coords + self.motion_transformation),
+                max_coords.max(max_coords + self.motion_transformation),
+            )
+        };
+
+        info.moving = (self.motion_transformation != Vec3A::ZERO
+            || self.motion_rotation != Vec3A::ZERO) as u32;
+        info.swept_min_bounds = swept_min.into();
+        info.swept_max_bounds = swept_max.into();
+    }
+
     pub fn set_model_to_surface(&mut self) {
         let transformation: Vec3A = self.object_info.max_bounds.into();
         let y_transform = transformation * Vec3A::Y;
@@ -224,16 +284,43 @@ fn generate_triangles(
     let triangles: Vec<SceneTriangle> = point_indexes
         .iter()
         .map(|indexes| {
-            SceneTriangle::new(
-                transformed_points[indexes[0]],
-                transformed_points[indexes[1]],
-                transformed_points[indexes[2]],
-            )
+            let a = transformed_points[indexes[0]];
+            let b = transformed_points[indexes[1]];
+            let c = transformed_points[indexes[2]];
+
+            let (tangent, tangent_sign) = generate_tangent(a, b, c);
+
+            SceneTriangle::new(a, b, c, tangent, tangent_sign)
         })
         .collect();
     triangles
 }
 
+fn generate_tangent(a: Vec3A, b: Vec3A, c: Vec3A) -> (Vec3A, f32) {
+    // objects are textured with a planar projection from above,
+    // so the u coordinate follows the X axis and the v coordinate follows the Z axis
+    let normal = (b - a).cross(c - a).normalize_or_zero();
+
+    let u_direction = Vec3A::X - normal * normal.dot(Vec3A::X);
+    let v_direction = Vec3A::Z - normal * normal.dot(Vec3A::Z);
+
+    // faces pointing along the X axis have no u gradient, build the tangent from the v direction instead
+    let tangent = if u_direction.length_squared() > 1e-6 {
+        u_direction.normalize()
+    } else {
+        v_direction.cross(normal).normalize_or(Vec3A::X)
+    };
+
+    // handedness of the bitangent, the shader calculates it as cross(normal, tangent) * sign
+    let tangent_sign = if normal.cross(tangent).dot(v_direction) < 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+
+    (tangent, tangent_sign)
+}
+
 fn normalize_model(mut points: Vec<Vec3A>, rotation_matrix: Vec3A) -> Vec<Vec3A> {
     points = rotate_to_angle(points, rotation_matrix);
 